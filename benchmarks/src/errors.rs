@@ -0,0 +1,95 @@
+use leptos_dom::{ErrorHasher, ErrorKey, Errors};
+use rayon::iter::ParallelIterator;
+use std::{error::Error, fmt};
+use test::Bencher;
+
+#[derive(Debug)]
+struct LookupError(usize);
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lookup {} failed", self.0)
+    }
+}
+
+impl Error for LookupError {}
+
+fn errors(count: usize) -> Errors {
+    errors_with_hasher(count, ErrorHasher::Std)
+}
+
+fn errors_with_hasher(count: usize, hasher: ErrorHasher) -> Errors {
+    let mut errors = Errors::with_hasher(hasher);
+    for i in 0..count {
+        errors.insert(ErrorKey::from(i.to_string()), LookupError(i));
+    }
+    errors
+}
+
+#[bench]
+fn errors_insert(b: &mut Bencher) {
+    let keys = (0..10_000)
+        .map(|i| ErrorKey::from(i.to_string()))
+        .collect::<Vec<_>>();
+    b.iter(|| {
+        let mut errors = Errors::default();
+        for (i, key) in keys.iter().enumerate() {
+            errors.insert(key.clone(), LookupError(i));
+        }
+        errors
+    });
+}
+
+fn lookup(b: &mut Bencher, hasher: ErrorHasher) {
+    let errors = errors_with_hasher(10_000, hasher);
+    let keys = (0..10_000)
+        .map(|i| ErrorKey::from(i.to_string()))
+        .collect::<Vec<_>>();
+    b.iter(|| {
+        keys.iter()
+            .filter(|key| errors.severity(key).is_some())
+            .count()
+    });
+}
+
+#[bench]
+fn errors_lookup(b: &mut Bencher) {
+    lookup(b, ErrorHasher::Std);
+}
+
+#[bench]
+fn errors_lookup_fx(b: &mut Bencher) {
+    lookup(b, ErrorHasher::Fx);
+}
+
+// stands in for an external lookup per error
+fn enrich(error: &(dyn Error + Send + Sync)) -> u64 {
+    let message = error.to_string();
+    (0..200).fold(0u64, |hash, i| {
+        message.bytes().fold(hash.wrapping_add(i), |hash, byte| {
+            hash.wrapping_mul(31).wrapping_add(byte as u64)
+        })
+    })
+}
+
+#[bench]
+fn errors_enrich_serial(b: &mut Bencher) {
+    let errors = errors(10_000);
+    b.iter(|| {
+        errors
+            .iter()
+            .map(|(_, error)| enrich(error.as_ref()))
+            .collect::<Vec<_>>()
+    });
+}
+
+#[bench]
+fn errors_enrich_parallel(b: &mut Bencher) {
+    let errors = errors(10_000);
+    b.iter(|| {
+        errors
+            .par_iter()
+            .map(|(_, error)| enrich(error.as_ref()))
+            .collect::<Vec<_>>()
+    });
+}
@@ -2,6 +2,7 @@
 
 extern crate test;
 
+mod errors;
 //åmod reactive;
 //mod ssr;
 mod todomvc;
@@ -0,0 +1,76 @@
+use leptos_dom::IntoView;
+use leptos_reactive::{signal_prelude::*, Scope, ScopeDisposer};
+use leptos_server::Action;
+use std::{cell::RefCell, error::Error, rc::Rc};
+
+/// Renders the most recent value of an [Action] that returns a `Result`,
+/// showing the `pending` view instead while the action is in flight.
+///
+/// Once the action resolves, its `Result` is rendered like any other: an `Err`
+/// is reported to the nearest `<ErrorBoundary/>`. Dispatching the action again
+/// replaces the previous result with the `pending` view, which clears the
+/// error it reported, so the boundary only shows errors of the latest call.
+///
+/// ```rust
+/// # use leptos::*;
+/// # run_scope(create_runtime(), |cx| {
+/// #[derive(Debug, Clone)]
+/// struct SaveError;
+///
+/// impl std::fmt::Display for SaveError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "could not save the todo")
+///     }
+/// }
+///
+/// impl std::error::Error for SaveError {}
+///
+/// async fn save_todo(task: String) -> Result<usize, SaveError> {
+///     // send the task to the API and return its id
+///     Ok(42)
+/// }
+///
+/// let save = create_action(cx, |task: &String| save_todo(task.clone()));
+///
+/// view! { cx,
+///   <button on:click=move |_| save.dispatch("My todo".to_string())>
+///     "Save"
+///   </button>
+///   <ErrorBoundary fallback=|cx, _| view! { cx, <p>"Saving failed."</p> }>
+///     {action_result(cx, save, |cx| view! { cx, <p>"Saving..."</p> })}
+///   </ErrorBoundary>
+/// }
+/// # });
+/// ```
+pub fn action_result<I, T, E, F, PV>(
+    cx: Scope,
+    action: Action<I, Result<T, E>>,
+    pending: F,
+) -> impl IntoView
+where
+    I: 'static,
+    T: IntoView + Clone + 'static,
+    E: Error + Clone + Send + Sync + 'static,
+    F: Fn(Scope) -> PV + 'static,
+    PV: IntoView,
+{
+    let is_pending = action.pending();
+    let value = action.value();
+    let prev_disposer = Rc::new(RefCell::new(None::<ScopeDisposer>));
+
+    move || {
+        // disposing the previous result's scope clears any error it reported
+        if let Some(disposer) = prev_disposer.take() {
+            disposer.dispose();
+        }
+        let (view, disposer) = cx.run_child_scope(|cx| {
+            if is_pending.get() {
+                pending(cx).into_view(cx)
+            } else {
+                value.get().into_view(cx)
+            }
+        });
+        *prev_disposer.borrow_mut() = Some(disposer);
+        view
+    }
+}
@@ -28,6 +28,36 @@ use leptos_reactive::{
 /// }
 /// # });
 /// ```
+///
+/// By default, the fallback replaces the children while there are errors. With
+/// `show_children_on_error`, the children stay visible instead, and the fallback
+/// is rendered right after them, e.g. so that a form stays editable while its
+/// errors are listed below it:
+///
+/// ```
+/// # use leptos_reactive::*;
+/// # use leptos_macro::*;
+/// # use leptos_dom::*; use leptos::*;
+/// # run_scope(create_runtime(), |cx| {
+/// let (value, set_value) = create_signal(cx, Ok(0));
+/// let on_input = move |ev| set_value(event_target_value(&ev).parse::<i32>());
+///
+/// view! { cx,
+///   <ErrorBoundary
+///     show_children_on_error=true
+///     fallback=move |_, _| view! { cx, <p class="error">"Enter a valid number."</p>}
+///   >
+///     <input type="text" on:input=on_input/>
+///     <p>"Value is: " {value}</p>
+///   </ErrorBoundary>
+/// }
+/// # });
+/// ```
+///
+/// As the boundary is transparent, the children and the fallback are siblings in
+/// the surrounding layout, so the fallback shifts the content that follows the
+/// boundary while it is shown. Wrap the boundary in an element if the errors
+/// should stay within a fixed region.
 #[component(transparent)]
 pub fn ErrorBoundary<F, IV>(
     cx: Scope,
@@ -35,6 +65,10 @@ pub fn ErrorBoundary<F, IV>(
     children: Children,
     /// A fallback that will be shown if an error occurs.
     fallback: F,
+    /// Whether the children stay visible while there are errors, with the
+    /// fallback rendered after them, instead of being replaced by it.
+    #[prop(optional)]
+    show_children_on_error: bool,
 ) -> impl IntoView
 where
     F: Fn(Scope, RwSignal<Errors>) -> IV + 'static,
@@ -48,9 +82,16 @@ where
     let children = children(cx);
 
     move || {
-        match errors.with(Errors::is_empty) {
-            true => children.clone().into_view(cx),
-            false => view! { cx,
+        match (errors.with(Errors::is_empty), show_children_on_error) {
+            (true, _) => children.clone().into_view(cx),
+            (false, true) => view! { cx,
+                <>
+                    {children.clone()}
+                    {fallback(cx, errors)}
+                </>
+            }
+            .into_view(cx),
+            (false, false) => view! { cx,
                 <>
                     {fallback(cx, errors)}
                     <leptos-error-boundary style="display: none">{children.clone()}</leptos-error-boundary>
@@ -172,6 +172,8 @@ pub use leptos_server::{
 };
 pub use server_fn::{self, ServerFn as _};
 pub use typed_builder;
+mod action_result;
+pub use action_result::*;
 mod error_boundary;
 pub use error_boundary::*;
 mod for_loop;
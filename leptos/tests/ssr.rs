@@ -198,3 +198,28 @@ fn ssr_option() {
         );
     });
 }
+
+#[cfg(not(any(feature = "csr", feature = "hydrate")))]
+#[test]
+fn ssr_error_boundary_shows_children_on_error() {
+    use leptos::*;
+
+    _ = create_scope(create_runtime(), |cx| {
+        let value = "abc".parse::<i32>();
+        let rendered = view! {
+            cx,
+            <ErrorBoundary
+                show_children_on_error=true
+                fallback=|cx, _| view! { cx, <p class="error">"Invalid"</p> }
+            >
+                <input type="text"/>
+                {value}
+            </ErrorBoundary>
+        };
+
+        let html = rendered.into_view(cx).render_to_string(cx);
+        assert!(html.contains("<input"));
+        assert!(html.contains("Invalid</p>"));
+        assert!(!html.contains("display: none"));
+    });
+}
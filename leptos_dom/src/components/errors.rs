@@ -1,172 +1,2700 @@
+//! Collection of the errors rendered by `Result` views, to be handled by
+//! an `<ErrorBoundary/>`.
+//!
+//! This module requires `std`, like the rest of `leptos_dom`, so it can't be
+//! used in `no_std` code. Besides the reactive system, it relies on the std
+//! hasher, on thread-locals, on a `Mutex` for the keys interned with the
+//! `intern-keys` feature, and on `SystemTime` for the time an error was
+//! inserted, or on `js_sys::Date` on `wasm32`.
+//!
+//! The stored errors implement [`std::error::Error`]. The `core-error`
+//! feature names the trait as `core::error::Error` instead, which requires
+//! Rust 1.81. Since then, `std::error::Error` is a re-export of the same
+//! trait, so both accept exactly the same error types.
+//!
+//! With the `tracing-error` feature, each error records the
+//! `SpanTrace` of the spans that were entered when it was inserted, outside of
+//! `wasm32`. Capturing requires a `tracing` subscriber with the `ErrorLayer`
+//! of `tracing-error` to be installed; otherwise nothing is recorded.
+
 use crate::{HydrationCtx, IntoView};
 use cfg_if::cfg_if;
-use leptos_reactive::{signal_prelude::*, use_context, RwSignal};
-use std::{borrow::Cow, collections::HashMap, error::Error, sync::Arc};
+#[cfg(feature = "core-error")]
+use core::error::Error;
+use indexmap::IndexMap;
+use leptos_reactive::{
+    create_isomorphic_effect, create_rw_signal, provide_context,
+    signal_prelude::*, use_context, RwSignal, Scope, ScopeDisposer, Signal,
+};
+#[cfg(not(feature = "core-error"))]
+use std::error::Error;
+use std::{
+    any::Any,
+    borrow::{Borrow, Cow},
+    collections::HashMap,
+    fmt,
+    rc::Rc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::Duration,
+};
+
+#[cfg(feature = "tokio")]
+mod broadcast;
+mod hasher;
+#[cfg(feature = "intern-keys")]
+mod intern;
+mod render;
+mod report;
+
+#[cfg(feature = "tokio")]
+pub use broadcast::*;
+pub use hasher::ErrorHasher;
+use hasher::MapHasher;
+#[cfg(feature = "intern-keys")]
+use intern::KeyStr;
+pub use render::*;
+pub use report::*;
+#[cfg(not(feature = "intern-keys"))]
+type KeyStr = Cow<'static, str>;
 
 /// A struct to hold all the possible errors that could be provided by child Views
-#[derive(Debug, Clone, Default)]
-#[repr(transparent)]
-pub struct Errors(HashMap<ErrorKey, Arc<dyn Error + Send + Sync>>);
+///
+/// Cloning [Errors] allocates a new map, but the errors themselves are
+/// shared behind an [Arc] rather than copied. See [`Errors::clone_shallow`].
+///
+/// The map uses the std hasher by default. Sets of errors whose keys never
+/// come from user input can use the faster, non-DoS-resistant `FxHasher`
+/// instead with [`Errors::with_hasher`], which helps with very large sets of
+/// errors on the server.
+///
+/// Either way, the hasher only affects lookups: errors are iterated in a
+/// well-defined order (see [`Errors::iter`]) that doesn't depend on its
+/// random seed, so tests can compare the errors in order without sorting
+/// them, and get the same result on every machine.
+#[derive(Clone, Default)]
+pub struct Errors {
+    /// The errors for the reserved keys come first, in the order in which the
+    /// keys were reserved, followed by the others in insertion order.
+    entries: IndexMap<ErrorKey, ErrorEntry, MapHasher>,
+    /// Bumped whenever an error is inserted or may have been modified in
+    /// place, which [`Errors::modify`] uses, along with the length, to tell
+    /// whether anything changed.
+    version: u64,
+    /// The keys reserved with [`Errors::reserve_keys`].
+    reserved: Vec<ErrorKey>,
+}
 
-/// A unique key for an error that occurs at a particular location in the user interface.
-#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
-#[repr(transparent)]
-pub struct ErrorKey(Cow<'static, str>);
+/// The position of `key` among the reserved keys, or `usize::MAX` if it is
+/// not reserved, so that errors for reserved keys sort first.
+fn reserved_slot(reserved: &[ErrorKey], key: &ErrorKey) -> usize {
+    reserved
+        .iter()
+        .position(|reserved| reserved == key)
+        .unwrap_or(usize::MAX)
+}
 
-impl<T> From<T> for ErrorKey
+impl fmt::Debug for Errors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+static NEXT_ENTRY_ID: AtomicU64 = AtomicU64::new(0);
+
+type ErrorRef<'a> = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>);
+
+#[cfg(feature = "tracing-error")]
+fn capture_span_trace() -> Option<tracing_error::SpanTrace> {
+    cfg_if! {
+        if #[cfg(target_arch = "wasm32")] {
+            None
+        } else {
+            use tracing_error::{SpanTrace, SpanTraceStatus};
+
+            let trace = SpanTrace::capture();
+            if trace.status() == SpanTraceStatus::CAPTURED {
+                Some(trace)
+            } else {
+                None
+            }
+        }
+    }
+}
+
+/// A single error stored in [Errors], along with its metadata.
+#[derive(Clone)]
+struct ErrorEntry {
+    error: Arc<dyn Error + Send + Sync>,
+    /// The same allocation as `error`, if its concrete type was known at
+    /// insertion, which allows downcasting to an owned `Arc<E>`. Only read
+    /// through [`ErrorEntry::typed_any`], since the error may have been
+    /// replaced through an [Entry] since then.
+    any: Option<Arc<dyn Any + Send + Sync>>,
+    /// Downcasts the error to a [UserFacing] type and returns its message.
+    user_message: Option<fn(&(dyn Error + 'static)) -> Option<String>>,
+    /// Downcasts the error to an [HtmlError] type and returns its HTML.
+    html: Option<fn(&(dyn Error + 'static)) -> Option<String>>,
+    /// Downcasts the error to a [DisplayHint] type and returns whether it
+    /// spans several lines.
+    multiline: Option<fn(&(dyn Error + 'static)) -> bool>,
+    /// When the error was inserted, as the time since the Unix epoch.
+    inserted_at: Duration,
+    severity: ErrorSeverity,
+    kind: ErrorKind,
+    /// Whether the user has seen the error, see [`Errors::acknowledge`].
+    acknowledged: bool,
+    /// Whether the error is left in place by the automatic cleanup of
+    /// `Result` views, see [`Errors::insert_sticky`].
+    sticky: bool,
+    /// Context attached to the error, which may be shared with the other
+    /// errors of a batch.
+    metadata: Option<Arc<HashMap<String, String>>>,
+    /// The [`Display`](fmt::Display) output of the error, if it was computed
+    /// once at insertion.
+    cached_message: Option<Arc<str>>,
+    /// The version of the [Errors] right after this error was inserted.
+    version: u64,
+    /// Identifies this particular insertion, even if its key is reused.
+    id: u64,
+    /// The spans that were entered when the error was inserted, boxed to
+    /// keep entries small when no trace was captured.
+    #[cfg(feature = "tracing-error")]
+    span_trace: Option<Box<tracing_error::SpanTrace>>,
+}
+
+impl ErrorEntry {
+    fn new(error: Arc<dyn Error + Send + Sync>) -> Self {
+        Self {
+            error,
+            any: None,
+            user_message: None,
+            html: None,
+            multiline: None,
+            inserted_at: now(),
+            severity: ErrorSeverity::default(),
+            kind: ErrorKind::default(),
+            acknowledged: false,
+            sticky: false,
+            metadata: None,
+            cached_message: None,
+            version: 0,
+            id: NEXT_ENTRY_ID.fetch_add(1, Ordering::Relaxed),
+            #[cfg(feature = "tracing-error")]
+            span_trace: capture_span_trace().map(Box::new),
+        }
+    }
+
+    /// The formatted span trace of the error, if one was captured.
+    fn span_trace_string(&self) -> Option<String> {
+        cfg_if! {
+            if #[cfg(feature = "tracing-error")] {
+                self.span_trace.as_ref().map(ToString::to_string)
+            } else {
+                None
+            }
+        }
+    }
+
+    fn typed<E>(error: E) -> Self
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        let error = Arc::new(error);
+        let mut entry = Self::new(error.clone());
+        entry.any = Some(error);
+        entry
+    }
+
+    /// The typed handle to the error, unless the error has been replaced
+    /// since it was inserted.
+    fn typed_any(&self) -> Option<&Arc<dyn Any + Send + Sync>> {
+        self.any.as_ref().filter(|any| {
+            Arc::as_ptr(any) as *const ()
+                == Arc::as_ptr(&self.error) as *const ()
+        })
+    }
+
+    /// Mutable access to the error, which may replace it, so the message
+    /// cached for the old error is dropped.
+    fn error_mut(&mut self) -> &mut Arc<dyn Error + Send + Sync> {
+        self.cached_message = None;
+        &mut self.error
+    }
+
+    /// Takes the owned `E` out of the entry, if it is the only reference to
+    /// an error of that type. Otherwise, gives the entry back unchanged.
+    // the entry is only moved back into the map it came from
+    #[allow(clippy::result_large_err)]
+    fn try_into_inner<E>(self) -> Result<E, Self>
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        if self.typed_any().is_none() || !self.error.is::<E>() {
+            return Err(self);
+        }
+
+        // release the second handle to the allocation, so it can be unwrapped
+        drop(self.error);
+        let error = self
+            .any
+            .and_then(|any| any.downcast::<E>().ok())
+            .expect("type was checked above");
+        Arc::try_unwrap(error).map_err(|error| Self {
+            error: error.clone(),
+            any: Some(error),
+            ..self
+        })
+    }
+
+    fn is_older_than(&self, max_age: Duration, now: Duration) -> bool {
+        now.saturating_sub(self.inserted_at) > max_age
+    }
+
+    /// The error that `user_message`, `html` and `multiline` downcast to the
+    /// type it was inserted with: the stored error, or the one it wraps if
+    /// [`Errors::add_context`] has wrapped it in [ContextError]s since.
+    fn hooked_error(&self) -> &(dyn Error + 'static) {
+        let mut error: &(dyn Error + 'static) = &*self.error;
+        while let Some(context) = error.downcast_ref::<ContextError>() {
+            error = &*context.source;
+        }
+        error
+    }
+
+    fn user_facing_message(&self) -> Option<String> {
+        self.user_message
+            .and_then(|user_message| user_message(self.hooked_error()))
+    }
+
+    /// The [`Display`](fmt::Display) output of the error, which is not run
+    /// again for errors inserted with [`Errors::insert_cached`], or its
+    /// [`Debug`](fmt::Debug) output if that is empty.
+    fn display_message(&self) -> String {
+        let message = match &self.cached_message {
+            Some(message) => message.to_string(),
+            None => self.error.to_string(),
+        };
+        if message.is_empty() {
+            format!("{:?}", self.error)
+        } else {
+            message
+        }
+    }
+
+    /// The message that the built-in views show for the error: its
+    /// [UserFacing] message, unless that is missing or empty, and its
+    /// [`display_message`](Self::display_message) otherwise, so that it is
+    /// never blank.
+    fn message(&self) -> String {
+        match self.user_facing_message() {
+            Some(message) if !message.is_empty() => message,
+            _ => self.display_message(),
+        }
+    }
+}
+
+/// An error that can provide a message that is safe to show to the users of
+/// an application, as opposed to its [`Display`](fmt::Display)
+/// implementation, which is often meant for developers and may leak internal
+/// details like SQL queries.
+///
+/// Errors inserted with [`Errors::insert_user_facing`] will prefer this
+/// message when rendered by the built-in error views.
+///
+/// This is the only way to attach the message: an `Err` rendered in a view,
+/// or passed to [`report_error`] or [`report_error_async`], is stored like
+/// any other error even if its type implements this trait, because the
+/// implementation can't be detected for an arbitrary error type. To show
+/// the user-facing message of such an error, insert it into the
+/// `RwSignal<Errors>` of the boundary with [`Errors::insert_user_facing`]
+/// instead, e.g. through [`use_errors`].
+///
+/// ```
+/// # use leptos_dom::{ErrorKey, Errors, UserFacing};
+/// #[derive(Debug)]
+/// struct DbError {
+///     query: String,
+/// }
+///
+/// impl std::fmt::Display for DbError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "query failed: {}", self.query)
+///     }
+/// }
+///
+/// impl std::error::Error for DbError {}
+///
+/// impl UserFacing for DbError {
+///     fn user_message(&self) -> String {
+///         "Something went wrong while loading your data.".to_string()
+///     }
+/// }
+///
+/// let mut errors = Errors::default();
+/// let key = ErrorKey::from("todos");
+/// let query = "SELECT * FROM todos".to_string();
+/// errors.insert_user_facing(key.clone(), DbError { query });
+/// assert_eq!(
+///     errors.user_message(&key).as_deref(),
+///     Some("Something went wrong while loading your data.")
+/// );
+/// ```
+pub trait UserFacing {
+    /// A description of the error that is safe to show to users.
+    fn user_message(&self) -> String;
+}
+
+/// The current time, as the time since the Unix epoch.
+fn now() -> Duration {
+    cfg_if! {
+      if #[cfg(target_arch = "wasm32")] {
+        Duration::from_secs_f64(js_sys::Date::now() / 1000.0)
+      } else {
+        use std::time::{SystemTime, UNIX_EPOCH};
+
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+      }
+    }
+}
+
+fn downcast_user_message<E>(error: &(dyn Error + 'static)) -> Option<String>
 where
-    T: Into<Cow<'static, str>>,
+    E: Error + UserFacing + 'static,
 {
+    error.downcast_ref::<E>().map(UserFacing::user_message)
+}
+
+/// How severe an error in [Errors] is.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorSeverity {
+    /// Something that should be brought to the user's attention, but did not
+    /// prevent anything from working.
+    Warning,
+    /// A regular error. This is the severity of errors inserted without one.
+    #[default]
+    Error,
+    /// An error that prevents a whole section of the application from
+    /// working.
+    Critical,
+}
+
+impl ErrorSeverity {
+    /// The lowercase name of the severity, e.g. `"warning"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Warning => "warning",
+            Self::Error => "error",
+            Self::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for ErrorSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Whether an error in [Errors] is worth retrying, e.g. to decide whether to
+/// offer a retry button.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ErrorKind {
+    /// A failure that may go away on its own, like a timeout or a dropped
+    /// connection.
+    Transient,
+    /// A failure that will happen again if retried, like a validation error.
+    /// This is the kind of errors inserted without one.
+    #[default]
+    Permanent,
+}
+
+/// Classifies an error as [`ErrorKind::Transient`] or
+/// [`ErrorKind::Permanent`] when it is inserted with
+/// [`Errors::insert_retryable`].
+pub trait Retryable {
+    /// Whether retrying the operation that failed may succeed.
+    fn is_transient(&self) -> bool;
+}
+
+/// A unique key for an error that occurs at a particular location in the user interface.
+///
+/// Keys are ordered lexicographically, so they can be used in a
+/// [`BTreeMap`](std::collections::BTreeMap) for sorted iteration.
+///
+/// With the `intern-keys` feature, keys created with [`ErrorKey::interned`]
+/// share a single allocation with every other interned key with the same
+/// contents, which saves memory when many errors are reported under a few
+/// distinct keys. Keys converted from strings, including the ones generated
+/// by the framework, are never interned. Interned and other keys compare,
+/// hash and sort exactly the same.
+#[derive(Debug, Default, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+#[repr(transparent)]
+pub struct ErrorKey(KeyStr);
+
+impl ErrorKey {
+    /// Creates a key from a string literal, without allocating.
     #[inline(always)]
-    fn from(key: T) -> ErrorKey {
+    pub const fn from_static(key: &'static str) -> Self {
+        #[cfg(feature = "intern-keys")]
+        let key = KeyStr::Static(key);
+        #[cfg(not(feature = "intern-keys"))]
+        let key = Cow::Borrowed(key);
+        Self(key)
+    }
+
+    /// Creates a key that shares its string with every other interned key
+    /// with the same contents.
+    ///
+    /// Interned strings are kept for the rest of the program, and interning
+    /// takes a global lock, so only intern keys from a small, fixed set, and
+    /// never keys that come from user input.
+    #[cfg(feature = "intern-keys")]
+    pub fn interned(key: &str) -> Self {
+        Self(KeyStr::intern(key))
+    }
+
+    /// Returns the key as a string slice.
+    #[inline(always)]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the [ErrorKeyPrefix] this key was namespaced with, e.g.
+    /// `billing` for `billing:0-1`, or `checkout/billing` for a prefix
+    /// nested within `checkout`.
+    pub fn namespace(&self) -> Option<&str> {
+        self.0.split_once(':').map(|(namespace, _)| namespace)
+    }
+}
+
+// these are implemented for each type, rather than for any
+// `T: Into<Cow<'static, str>>`, so as not to overlap with `ErrorKeyEnum`
+impl From<&'static str> for ErrorKey {
+    #[inline(always)]
+    fn from(key: &'static str) -> ErrorKey {
         ErrorKey(key.into())
     }
 }
 
-impl IntoIterator for Errors {
-    type Item = (ErrorKey, Arc<dyn Error + Send + Sync>);
-    type IntoIter = IntoIter;
+impl From<String> for ErrorKey {
+    #[inline(always)]
+    fn from(key: String) -> ErrorKey {
+        ErrorKey(key.into())
+    }
+}
 
+impl From<&'static String> for ErrorKey {
     #[inline(always)]
-    fn into_iter(self) -> Self::IntoIter {
-        IntoIter(self.0.into_iter())
+    fn from(key: &'static String) -> ErrorKey {
+        ErrorKey(key.into())
     }
 }
 
-/// An owning iterator over all the errors contained in the [Errors] struct.
-#[repr(transparent)]
-pub struct IntoIter(
-    std::collections::hash_map::IntoIter<
-        ErrorKey,
-        Arc<dyn Error + Send + Sync>,
-    >,
-);
+impl From<Cow<'static, str>> for ErrorKey {
+    #[inline(always)]
+    // a no-op unless the key is interned
+    #[allow(clippy::useless_conversion)]
+    fn from(key: Cow<'static, str>) -> ErrorKey {
+        ErrorKey(key.into())
+    }
+}
 
-impl Iterator for IntoIter {
-    type Item = (ErrorKey, Arc<dyn Error + Send + Sync>);
+/// An enum whose variants are keys for errors, which makes typos in keys
+/// impossible, e.g. for the fields of a form.
+///
+/// ```
+/// # use leptos_dom::{ErrorKey, ErrorKeyEnum};
+/// enum SignupField {
+///     Email,
+///     Password,
+/// }
+///
+/// impl ErrorKeyEnum for SignupField {
+///     fn as_key_str(&self) -> &'static str {
+///         match self {
+///             Self::Email => "signup-email",
+///             Self::Password => "signup-password",
+///         }
+///     }
+/// }
+///
+/// assert_eq!(ErrorKey::from(SignupField::Email).as_str(), "signup-email");
+/// ```
+pub trait ErrorKeyEnum {
+    /// The key for this variant. This should be stable, and distinct for
+    /// each variant.
+    fn as_key_str(&self) -> &'static str;
+}
 
+impl<T> From<T> for ErrorKey
+where
+    T: ErrorKeyEnum,
+{
     #[inline(always)]
-    fn next(
-        &mut self,
-    ) -> std::option::Option<<Self as std::iter::Iterator>::Item> {
-        self.0.next()
+    fn from(key: T) -> ErrorKey {
+        ErrorKey::from_static(key.as_key_str())
     }
 }
 
-/// An iterator over all the errors contained in the [Errors] struct.
+/// Keys hash and compare exactly like their strings, so a map keyed by
+/// [ErrorKey] can be looked up with a `&str`.
+impl Borrow<str> for ErrorKey {
+    #[inline(always)]
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+/// A namespace for the keys of the errors reported by `Result` views within
+/// part of the view tree, set with [`provide_error_key_prefix`].
+///
+/// Keys derived under a prefix have the form `{prefix}:{id}`. Prefixes
+/// provided under another prefix are nested as `{outer}/{inner}`, so that
+/// [`clear_scope`] for the outer prefix also clears the inner one.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 #[repr(transparent)]
-pub struct Iter<'a>(
-    std::collections::hash_map::Iter<
-        'a,
-        ErrorKey,
-        Arc<dyn Error + Send + Sync>,
-    >,
-);
+pub struct ErrorKeyPrefix(Cow<'static, str>);
 
-impl<'a> Iterator for Iter<'a> {
-    type Item = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>);
+impl ErrorKeyPrefix {
+    /// Creates a new prefix.
+    #[inline(always)]
+    pub fn new(prefix: impl Into<Cow<'static, str>>) -> Self {
+        Self(prefix.into())
+    }
 
+    /// Returns the prefix as a string slice.
     #[inline(always)]
-    fn next(
-        &mut self,
-    ) -> std::option::Option<<Self as std::iter::Iterator>::Item> {
-        self.0.next()
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Creates a key namespaced by this prefix.
+    pub fn key(&self, id: impl fmt::Display) -> ErrorKey {
+        ErrorKey(format!("{}:{id}", self.0).into())
+    }
+
+    /// Returns `true` if the key is namespaced by this prefix, or by a prefix
+    /// nested within it.
+    pub fn contains(&self, key: &ErrorKey) -> bool {
+        key.0
+            .strip_prefix(&*self.0)
+            .map(|rest| rest.starts_with(':') || rest.starts_with('/'))
+            .unwrap_or(false)
     }
 }
 
-impl<T, E> IntoView for Result<T, E>
+/// Namespaces the keys of the errors reported by `Result` views in this scope
+/// and its children with the given prefix. If there is already a prefix in
+/// this scope, the new one is nested within it.
+///
+/// This lets a reusable component manage the lifecycle of its own errors
+/// with [`clear_scope`], without knowing the keys used by other components.
+pub fn provide_error_key_prefix(
+    cx: Scope,
+    prefix: impl Into<Cow<'static, str>>,
+) {
+    let prefix = prefix.into();
+    let prefix = match use_context::<ErrorKeyPrefix>(cx) {
+        Some(parent) => format!("{}/{prefix}", parent.0).into(),
+        None => prefix,
+    };
+    provide_context(cx, ErrorKeyPrefix(prefix));
+}
+
+/// The hydration path of the nearest element provided with
+/// [`provide_error_key_anchor`].
+#[derive(Clone)]
+struct ErrorKeyAnchor {
+    name: Cow<'static, str>,
+    path: String,
+}
+
+/// Makes the keys of the errors reported by `Result` views in this scope and
+/// its children relative to this point in the view tree.
+///
+/// By default, the key of an error is the hydration id of the component that
+/// rendered it, which depends on every component rendered before it. If a
+/// condition before the view is momentarily different on the server and the
+/// client, e.g. behind a `<Show/>`, the ids drift, and the same error gets a
+/// different key on each side. Within an anchor, keys are composed of the
+/// anchor's `name` and the path of the component from the anchor, as
+/// `{name}@{path}`, so they only depend on what is rendered inside it. An
+/// [ErrorKeyPrefix] still applies, as `{prefix}:{name}@{path}`.
+///
+/// The `name` should be unique among the anchors that share a boundary.
+pub fn provide_error_key_anchor(cx: Scope, name: impl Into<Cow<'static, str>>) {
+    let path = HydrationCtx::with_previous(str::to_owned);
+    provide_context(
+        cx,
+        ErrorKeyAnchor {
+            name: name.into(),
+            path,
+        },
+    );
+}
+
+/// Generates the keys of the errors rendered by `Result` views, in place of
+/// the default keys derived from the hydration id of the view.
+///
+/// This gives an application control over the identity of its errors, e.g.
+/// to key them by a counter, or by the item of a list they belong to, so
+/// that reordering the list doesn't mix up their errors. An
+/// [ErrorKeyPrefix] still applies to the generated keys, as
+/// `{prefix}:{key}`, while [`provide_error_key_anchor`] doesn't.
+///
+/// Closures taking a [Scope] and returning an [ErrorKey] implement this
+/// trait. Note that the key is generated every time a `Result` view
+/// renders, be it `Ok` or `Err`, since an `Ok` value clears the error
+/// rendered at its key before.
+pub trait KeyStrategy {
+    /// Generates the key for a `Result` view rendered in `cx`.
+    fn key(&self, cx: Scope) -> ErrorKey;
+}
+
+impl<F> KeyStrategy for F
 where
-    T: IntoView + 'static,
-    E: Error + Send + Sync + 'static,
+    F: Fn(Scope) -> ErrorKey,
 {
-    fn into_view(self, cx: leptos_reactive::Scope) -> crate::View {
-        let id = ErrorKey(HydrationCtx::peek().previous.into());
-        let errors = use_context::<RwSignal<Errors>>(cx);
-        match self {
-            Ok(stuff) => {
-                if let Some(errors) = errors {
-                    errors.update(|errors| {
-                        errors.0.remove(&id);
+    fn key(&self, cx: Scope) -> ErrorKey {
+        self(cx)
+    }
+}
+
+/// Generates the keys of the errors of `Result` views in this scope and its
+/// children with `strategy`. See [KeyStrategy].
+pub fn provide_key_strategy(cx: Scope, strategy: impl KeyStrategy + 'static) {
+    provide_context::<Rc<dyn KeyStrategy>>(cx, Rc::new(strategy));
+}
+
+/// Removes every error whose key is namespaced by the [ErrorKeyPrefix] of
+/// this scope, leaving the errors of other components untouched. This can be
+/// called when a component is torn down, e.g., in
+/// [`on_cleanup`](leptos_reactive::on_cleanup).
+///
+/// Does nothing if no prefix was provided with [`provide_error_key_prefix`].
+pub fn clear_scope(cx: Scope, errors: RwSignal<Errors>) {
+    if let Some(prefix) = use_context::<ErrorKeyPrefix>(cx) {
+        let has_errors = errors.with_untracked(|errors| {
+            errors.entries.keys().any(|key| prefix.contains(key))
+        });
+        if has_errors {
+            errors.update(|errors| {
+                errors.remove_with_prefix(&prefix);
+            });
+        }
+    }
+}
+
+/// Returns `true` if there is an `<ErrorBoundary/>` above this scope to
+/// handle errors returned from `Result` views.
+///
+/// Without one, errors are dropped unless there is an [ErrorSink], so this
+/// lets a component choose to render a safe default rather than an error.
+/// This is always `false` in a subtree disconnected with
+/// [`suppress_errors_in_subtree`].
+///
+/// ```
+/// # use leptos_dom::{has_error_boundary, Errors};
+/// # use leptos_reactive::*;
+/// # create_scope(create_runtime(), |cx| {
+/// assert!(!has_error_boundary(cx));
+///
+/// // this is what `<ErrorBoundary/>` does
+/// provide_context(cx, create_rw_signal(cx, Errors::default()));
+/// assert!(has_error_boundary(cx));
+/// # }).dispose();
+/// ```
+///
+/// An [ErrorSink] is not a boundary: see [`has_error_handler`] to check for
+/// either.
+pub fn has_error_boundary(cx: Scope) -> bool {
+    use_errors(cx).is_some()
+}
+
+/// Returns `true` if errors returned from `Result` views in this scope are
+/// handled at all, either by an `<ErrorBoundary/>` or by an [ErrorSink]
+/// from [`provide_error_sink`]. This is always `false` in a subtree
+/// disconnected with [`suppress_errors_in_subtree`].
+pub fn has_error_handler(cx: Scope) -> bool {
+    !is_subtree_suppressed(cx)
+        && (use_context::<RwSignal<Errors>>(cx).is_some()
+            || use_context::<Rc<dyn ErrorSink>>(cx).is_some())
+}
+
+/// The context provided by [`suppress_errors_in_subtree`].
+#[derive(Clone, Copy)]
+struct SuppressedSubtree;
+
+/// Disconnects this scope and its children from error collection, e.g. for
+/// a third-party embed whose errors should not show up in the application's
+/// `<ErrorBoundary/>`.
+///
+/// Errors rendered or reported in the subtree are neither shown nor logged,
+/// even without a boundary, and even in debug builds. This also applies to
+/// any `<ErrorBoundary/>` inside the subtree. Unlike
+/// [`provide_error_filter`], this drops every error, without looking at it.
+pub fn suppress_errors_in_subtree(cx: Scope) {
+    provide_context(cx, SuppressedSubtree);
+}
+
+fn is_subtree_suppressed(cx: Scope) -> bool {
+    use_context::<SuppressedSubtree>(cx).is_some()
+}
+
+type Filter = Rc<dyn Fn(&(dyn Error + 'static)) -> bool>;
+
+/// The filters provided by [`provide_error_filter`] in this scope and its
+/// parents.
+#[derive(Clone, Default)]
+struct ErrorFilters(Vec<Filter>);
+
+/// Provides a predicate that suppresses errors in this scope and its children,
+/// e.g. errors for requests the user cancelled. An error is suppressed if this
+/// or any filter provided in a parent scope returns `true` for it.
+///
+/// Suppressed errors never enter [Errors]: `Result` views and
+/// [`report_error`] drop them, and only log them at the `debug` level in
+/// debug builds.
+pub fn provide_error_filter(
+    cx: Scope,
+    filter: impl Fn(&(dyn Error + 'static)) -> bool + 'static,
+) {
+    let mut filters = use_context::<ErrorFilters>(cx).unwrap_or_default();
+    filters.0.push(Rc::new(filter));
+    provide_context(cx, filters);
+}
+
+fn is_suppressed(cx: Scope, error: &(dyn Error + 'static)) -> bool {
+    use_context::<ErrorFilters>(cx)
+        .map(|filters| filters.0.iter().any(|filter| filter(error)))
+        .unwrap_or(false)
+}
+
+type Enricher =
+    Rc<dyn Fn(Arc<dyn Error + Send + Sync>) -> Arc<dyn Error + Send + Sync>>;
+
+/// The enrichers provided by [`provide_error_enricher`] in this scope and its
+/// parents, outermost first.
+#[derive(Clone, Default)]
+struct ErrorEnrichers(Vec<Enricher>);
+
+impl ErrorEnrichers {
+    fn apply(
+        &self,
+        error: Arc<dyn Error + Send + Sync>,
+    ) -> Arc<dyn Error + Send + Sync> {
+        self.0
+            .iter()
+            .rev()
+            .fold(error, |error, enrich| enrich(error))
+    }
+}
+
+/// Provides a function that transforms every error reported in this scope
+/// and its children before it is stored, e.g. to wrap it in a
+/// [ContextError] with the id of the current request.
+///
+/// Errors suppressed by [`provide_error_filter`] are dropped before they are
+/// enriched, so filters always see the original error. If several enrichers
+/// apply, the innermost one runs first, and the outermost one wraps the
+/// result last.
+///
+/// Enriched errors are stored behind the [Arc] returned by the enricher, so
+/// they can't be taken out of [Errors] as their original, owned type.
+pub fn provide_error_enricher(
+    cx: Scope,
+    enricher: impl Fn(Arc<dyn Error + Send + Sync>) -> Arc<dyn Error + Send + Sync>
+        + 'static,
+) {
+    let mut enrichers = use_context::<ErrorEnrichers>(cx).unwrap_or_default();
+    enrichers.0.push(Rc::new(enricher));
+    provide_context(cx, enrichers);
+}
+
+/// A container that `Result` views and [`report_error`] report errors to
+/// when there is no `<ErrorBoundary/>`, e.g. to collect errors while
+/// rendering headlessly on the server or in tests.
+///
+/// Provide one with [`provide_error_sink`]. Errors go to the first of these
+/// that is available:
+/// 1. the `RwSignal<Errors>` of the nearest `<ErrorBoundary/>`,
+/// 2. the nearest sink,
+/// 3. nowhere, with a warning in debug builds.
+pub trait ErrorSink {
+    /// Stores `error` under `key`, replacing any previous error.
+    fn report(&self, key: ErrorKey, error: Arc<dyn Error + Send + Sync>);
+
+    /// Removes the error with the given key, if any.
+    fn remove(&self, key: &ErrorKey);
+}
+
+/// Like an `<ErrorBoundary/>`, the errors are left in place if they were
+/// made [sticky](Errors::insert_sticky).
+impl ErrorSink for RwSignal<Errors> {
+    fn report(&self, key: ErrorKey, error: Arc<dyn Error + Send + Sync>) {
+        self.update(|errors| errors.insert_entry(key, ErrorEntry::new(error)));
+    }
+
+    fn remove(&self, key: &ErrorKey) {
+        let is_clearable = self.with_untracked(|errors| {
+            matches!(errors.entries.get(key), Some(entry) if !entry.sticky)
+        });
+        if is_clearable {
+            self.update(|errors| {
+                errors.entries.shift_remove(key);
+            });
+        }
+    }
+}
+
+/// Sends each reported error through the channel, e.g. to collect the errors
+/// of a page rendered on the server.
+///
+/// Errors that are removed again are not retracted, so the receiver sees
+/// every error that was reported, even if its `Result` later became `Ok`.
+/// Errors reported after the receiver was dropped are discarded.
+impl ErrorSink for mpsc::Sender<(ErrorKey, Arc<dyn Error + Send + Sync>)> {
+    fn report(&self, key: ErrorKey, error: Arc<dyn Error + Send + Sync>) {
+        _ = self.send((key, error));
+    }
+
+    fn remove(&self, _key: &ErrorKey) {}
+}
+
+/// Provides an [ErrorSink] that `Result` views in this scope and its children
+/// report their errors to, unless they are inside an `<ErrorBoundary/>`.
+pub fn provide_error_sink(cx: Scope, sink: impl ErrorSink + 'static) {
+    provide_context::<Rc<dyn ErrorSink>>(cx, Rc::new(sink));
+}
+
+thread_local! {
+    /// The id of the latest error reported to each sink under each key, so
+    /// that cleaning up an error doesn't remove a newer one with the same key.
+    static SINK_REPORTS: std::cell::RefCell<
+        std::collections::HashMap<(usize, ErrorKey), u64>,
+    > = Default::default();
+}
+
+fn report_to_sink(
+    cx: Scope,
+    sink: Rc<dyn ErrorSink>,
+    key: ErrorKey,
+    error: Arc<dyn Error + Send + Sync>,
+) {
+    let id = NEXT_ENTRY_ID.fetch_add(1, Ordering::Relaxed);
+    let report = (Rc::as_ptr(&sink) as *const () as usize, key.clone());
+    SINK_REPORTS
+        .with(|reports| reports.borrow_mut().insert(report.clone(), id));
+    sink.report(key, error);
+
+    // like errors in an `RwSignal<Errors>`, but keyed by sink
+    let cleanup = move || {
+        let is_current = SINK_REPORTS.with(|reports| {
+            let mut reports = reports.borrow_mut();
+            if reports.get(&report) == Some(&id) {
+                reports.remove(&report);
+                true
+            } else {
+                false
+            }
+        });
+        if is_current {
+            sink.remove(&report.1);
+        }
+    };
+    leptos_reactive::on_cleanup(cx, move || {
+        cfg_if! {
+          if #[cfg(all(target_arch = "wasm32", feature = "web"))] {
+            queue_cleanup(cleanup);
+          } else {
+            cleanup();
+          }
+        }
+    });
+}
+
+/// Removes the error that was inserted into `errors` under `key` with the
+/// given id, as returned by [`Errors::iter_with_ids`], unless it has been
+/// replaced in the meantime or is [sticky](Errors::insert_sticky). Returns
+/// `true` if it was removed.
+///
+/// This is how [`report_error`] removes an error once the scope it was
+/// reported from is disposed, e.g. when a `Result` view re-renders. Only
+/// when this runs depends on the target: on the server, it runs as soon as
+/// the scope is disposed. In the browser, it runs in a microtask, after a
+/// view that re-renders from `Err` to `Err` has inserted its new error under
+/// the same key, which is then kept.
+///
+/// It is public so that this behavior can be tested without a DOM, by
+/// calling it in either order relative to a new insertion.
+pub fn cleanup_error(
+    errors: RwSignal<Errors>,
+    key: &ErrorKey,
+    id: u64,
+) -> bool {
+    let is_current = errors
+        .try_with_untracked(|errors| {
+            matches!(
+                errors.entries.get(key),
+                Some(entry) if entry.id == id && !entry.sticky
+            )
+        })
+        .unwrap_or(false);
+    if is_current {
+        errors.update(|errors: &mut Errors| {
+            errors.entries.shift_remove(key);
+        });
+    }
+    is_current
+}
+
+/// Removes the error with the given key from wherever [`report_error`] would
+/// report it, for a `Result` view that is now `Ok`. Sticky errors are kept.
+fn clear_error(cx: Scope, key: &str) {
+    if let Some(errors) = use_context::<RwSignal<Errors>>(cx) {
+        // avoid notifying the boundary if there was no error
+        let is_clearable = errors.with_untracked(|errors| {
+            matches!(errors.entries.get(key), Some(entry) if !entry.sticky)
+        });
+        if is_clearable {
+            errors.update(|errors| {
+                errors.entries.shift_remove(key);
+            });
+        }
+    } else if let Some(sink) = use_context::<Rc<dyn ErrorSink>>(cx) {
+        sink.remove(&ErrorKey::from(key.to_owned()));
+    }
+}
+
+/// Reports `error` under `key` to the nearest `<ErrorBoundary/>`, with the
+/// same semantics as rendering an `Err` in a view: the error is removed
+/// again when `cx` is disposed, unless it has been replaced by a newer error
+/// under the same key in the meantime. See [`cleanup_error`].
+///
+/// Without an `<ErrorBoundary/>`, the error is dropped, with a warning in
+/// debug builds the first time this happens for each key. Errors suppressed
+/// by [`provide_error_filter`] are dropped as well, and so are all errors in
+/// a subtree disconnected with [`suppress_errors_in_subtree`]. Other errors
+/// are transformed by the enrichers of [`provide_error_enricher`] before
+/// they are stored.
+pub fn report_error(
+    cx: Scope,
+    key: ErrorKey,
+    error: impl Error + Send + Sync + 'static,
+) {
+    if is_subtree_suppressed(cx) {
+        return;
+    }
+    if is_suppressed(cx, &error) {
+        #[cfg(debug_assertions)]
+        debug!(key = key.as_str(), error = %error, "suppressed error");
+        return;
+    }
+
+    let enrichers = use_context::<ErrorEnrichers>(cx);
+    match (
+        use_context::<RwSignal<Errors>>(cx),
+        use_context::<Rc<dyn ErrorSink>>(cx),
+    ) {
+        (Some(errors), _) => {
+            let entry = match enrichers {
+                Some(enrichers) => {
+                    ErrorEntry::new(enrichers.apply(Arc::new(error)))
+                }
+                None => ErrorEntry::typed(error),
+            };
+            let (cleanup_key, entry_id) = (key.clone(), entry.id);
+            errors.update(move |errors: &mut Errors| {
+                errors.insert_entry(key, entry);
+            });
+
+            // remove the error from the list if this drops,
+            // i.e., if it's in a DynChild that switches from Err to Ok
+            leptos_reactive::on_cleanup(cx, move || {
+                cfg_if! {
+                  if #[cfg(all(target_arch = "wasm32", feature = "web"))] {
+                    // a DynChild that re-runs from Err to Err inserts its
+                    // new error under the same key before this runs
+                    queue_cleanup(move || {
+                      cleanup_error(errors, &cleanup_key, entry_id);
                     });
+                  } else {
+                    cleanup_error(errors, &cleanup_key, entry_id);
+                  }
                 }
-                stuff.into_view(cx)
+            });
+        }
+        (None, Some(sink)) => {
+            let mut error: Arc<dyn Error + Send + Sync> = Arc::new(error);
+            if let Some(enrichers) = enrichers {
+                error = enrichers.apply(error);
             }
-            Err(error) => {
-                match errors {
-                    Some(errors) => {
-                        errors.update({
-                            #[cfg(all(
-                                target_arch = "wasm32",
-                                feature = "web"
-                            ))]
-                            let id = id.clone();
-                            move |errors: &mut Errors| errors.insert(id, error)
-                        });
-
-                        // remove the error from the list if this drops,
-                        // i.e., if it's in a DynChild that switches from Err to Ok
-                        // Only can run on the client, will panic on the server
-                        cfg_if! {
-                          if #[cfg(all(target_arch = "wasm32", feature = "web"))] {
-                            use leptos_reactive::{on_cleanup, queue_microtask};
-                            on_cleanup(cx, move || {
-                              queue_microtask(move || {
-                                errors.update(|errors: &mut Errors| {
-                                  errors.remove(&id);
-                                });
-                              });
-                            });
-                          }
-                        }
-                    }
-                    None => {
-                        #[cfg(debug_assertions)]
-                        warn!(
-                            "No ErrorBoundary components found! Returning \
-                             errors will not be handled and will silently \
-                             disappear"
-                        );
-                    }
-                }
-                ().into_view(cx)
+            report_to_sink(cx, sink, key, error);
+        }
+        (None, None) => {
+            #[cfg(debug_assertions)]
+            if first_unhandled(&key) {
+                warn!(
+                    key = key.as_str(),
+                    hydration_id = %HydrationCtx::peek(),
+                    error = %error,
+                    "No ErrorBoundary components found! Returning errors \
+                     will not be handled and will silently disappear"
+                );
             }
+            #[cfg(not(debug_assertions))]
+            let _ = (key, error);
         }
     }
 }
-impl Errors {
-    /// Returns `true` if there are no errors.
-    #[inline(always)]
-    pub fn is_empty(&self) -> bool {
-        self.0.is_empty()
+
+/// Returns `true` the first time an error is dropped for lack of a boundary
+/// under `key`, so that re-rendering an unhandled `Result` doesn't flood the
+/// console with the same warning.
+#[cfg(debug_assertions)]
+fn first_unhandled(key: &ErrorKey) -> bool {
+    thread_local! {
+        static WARNED: std::cell::RefCell<std::collections::HashSet<ErrorKey>> =
+            Default::default();
     }
+    WARNED.with(|warned| warned.borrow_mut().insert(key.clone()))
+}
 
-    /// Add an error to Errors that will be processed by `<ErrorBoundary/>`
-    pub fn insert<E>(&mut self, key: ErrorKey, error: E)
-    where
-        E: Error + Send + Sync + 'static,
-    {
-        self.0.insert(key, Arc::new(error));
+/// Panics if the `<ErrorBoundary/>` above this scope has collected any
+/// errors, listing them in the panic message. See [`Errors::assert_empty`].
+///
+/// Does nothing if there is no `<ErrorBoundary/>`, as errors are dropped
+/// without one.
+#[track_caller]
+pub fn assert_no_errors(cx: Scope) {
+    if let Some(errors) = use_context::<RwSignal<Errors>>(cx) {
+        errors.with_untracked(Errors::assert_empty);
     }
+}
 
-    /// Add an error with the default key for errors outside the reactive system
-    pub fn insert_with_default_key<E>(&mut self, error: E)
-    where
-        E: Error + Send + Sync + 'static,
-    {
-        self.0.insert(Default::default(), Arc::new(error));
+/// Returns the errors of the nearest `<ErrorBoundary/>`, if there is one,
+/// and this scope is not in a subtree disconnected with
+/// [`suppress_errors_in_subtree`].
+///
+/// This is how to report errors from a future that outlives the current
+/// scope: get the signal before spawning the future, and pass it to
+/// [`report_error_async`] from within it.
+pub fn use_errors(cx: Scope) -> Option<RwSignal<Errors>> {
+    if is_subtree_suppressed(cx) {
+        return None;
     }
+    use_context::<RwSignal<Errors>>(cx)
+}
 
-    /// Remove an error to Errors that will be processed by `<ErrorBoundary/>`
-    pub fn remove(
-        &mut self,
-        key: &ErrorKey,
-    ) -> Option<Arc<dyn Error + Send + Sync>> {
-        self.0.remove(key)
+/// Reports `error` under `key` to `errors`, which is typically obtained with
+/// [`use_errors`] before spawning a future, since the scope that spawned the
+/// future may be gone by the time it fails.
+///
+/// ```
+/// # use leptos_dom::{report_error_async, use_errors, ErrorKey, Errors};
+/// # use leptos_reactive::*;
+/// # #[derive(Debug)]
+/// # struct SaveError;
+/// # impl std::fmt::Display for SaveError {
+/// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+/// #         f.write_str("could not save")
+/// #     }
+/// # }
+/// # impl std::error::Error for SaveError {}
+/// # async fn save() -> Result<(), SaveError> { Err(SaveError) }
+/// # // `spawn_local` needs a tokio `LocalSet` to run in with `ssr`
+/// # #[cfg(not(feature = "ssr"))]
+/// # create_scope(create_runtime(), |cx| {
+/// # provide_context(cx, create_rw_signal(cx, Errors::default()));
+/// if let Some(errors) = use_errors(cx) {
+///     spawn_local(async move {
+///         if let Err(error) = save().await {
+///             report_error_async(errors, ErrorKey::from("save"), error);
+///         }
+///     });
+/// }
+/// # }).dispose();
+/// ```
+///
+/// Unlike [`report_error`], the error is not tied to a scope, so it is not
+/// removed automatically, and the filters of [`provide_error_filter`] don't
+/// apply. Does nothing if the boundary has been disposed in the meantime.
+pub fn report_error_async(
+    errors: RwSignal<Errors>,
+    key: ErrorKey,
+    error: impl Error + Send + Sync + 'static,
+) {
+    let entry = ErrorEntry::typed(error);
+    errors.try_update(move |errors| errors.insert_entry(key, entry));
+}
+
+thread_local! {
+    /// The base keys of the `Ok` results whose values are being rendered.
+    static RENDERING_OK: std::cell::RefCell<KeyStack> = Default::default();
+    /// Holds the base key of an `Ok` result while it is looked up, and keeps
+    /// its capacity from one render to the next.
+    static KEY_BUFFER: std::cell::Cell<String> = Default::default();
+}
+
+/// A stack of keys, innermost last, concatenated into a single buffer so
+/// that pushing a key doesn't allocate once the buffer is large enough.
+#[derive(Default)]
+struct KeyStack {
+    keys: String,
+    ends: Vec<usize>,
+}
+
+impl KeyStack {
+    fn push(&mut self, key: &str) {
+        self.keys.push_str(key);
+        self.ends.push(self.keys.len());
     }
 
-    /// An iterator over all the errors, in arbitrary order.
-    #[inline(always)]
-    pub fn iter(&self) -> Iter<'_> {
-        Iter(self.0.iter())
+    fn pop(&mut self) {
+        self.ends.pop();
+        self.keys.truncate(self.ends.last().copied().unwrap_or(0));
+    }
+
+    /// The number of keys on the stack equal to `key`.
+    fn count(&self, key: &str) -> usize {
+        let mut start = 0;
+        self.ends
+            .iter()
+            .filter(|&&end| {
+                let matches = &self.keys[start..end] == key;
+                start = end;
+                matches
+            })
+            .count()
+    }
+}
+
+/// Derives the key for an error rendered at the current location in the
+/// view tree.
+///
+/// A `Result` rendered directly as the `Ok` value of another one is at the
+/// same location, so it would get the same key. It gets the key suffixed
+/// with its nesting depth instead, e.g. `0-1/1`, so an error of the inner
+/// `Result` doesn't replace one of the outer, and keys stay the same from
+/// one render to the next.
+fn derive_key(cx: Scope) -> ErrorKey {
+    let base = base_key(cx);
+    match nesting_depth(base.as_str()) {
+        0 => base,
+        depth => nested_key(base.as_str(), depth),
+    }
+}
+
+/// The number of `Ok` results with the same base key whose values are being
+/// rendered.
+fn nesting_depth(base: &str) -> usize {
+    RENDERING_OK.with(|keys| keys.borrow().count(base))
+}
+
+fn nested_key(base: &str, depth: usize) -> ErrorKey {
+    ErrorKey::from(format!("{base}/{depth}"))
+}
+
+/// Derives the key for the current location in the view tree, without
+/// accounting for nested `Result`s.
+fn base_key(cx: Scope) -> ErrorKey {
+    // a key from a strategy is kept as it is, e.g. static or interned
+    if use_context::<ErrorKeyPrefix>(cx).is_none() {
+        if let Some(strategy) = use_context::<Rc<dyn KeyStrategy>>(cx) {
+            return strategy.key(cx);
+        }
+    }
+    let mut key = String::new();
+    write_base_key(cx, &mut key);
+    ErrorKey::from(key)
+}
+
+/// Appends the key of [`base_key`] to `out`.
+///
+/// This is on the hot path of every `Ok` result inside an
+/// `<ErrorBoundary/>`, so it borrows the hydration id rather than cloning
+/// it, and only allocates if `out` has to grow or the key comes from a
+/// [KeyStrategy].
+fn write_base_key(cx: Scope, out: &mut String) {
+    if let Some(prefix) = use_context::<ErrorKeyPrefix>(cx) {
+        out.push_str(&prefix.0);
+        out.push(':');
+    }
+    if let Some(strategy) = use_context::<Rc<dyn KeyStrategy>>(cx) {
+        out.push_str(strategy.key(cx).as_str());
+        return;
+    }
+
+    let anchor = use_context::<ErrorKeyAnchor>(cx);
+    HydrationCtx::with_previous(|id| match &anchor {
+        Some(anchor) => {
+            out.push_str(&anchor.name);
+            out.push('@');
+            out.push_str(id.strip_prefix(anchor.path.as_str()).unwrap_or(id));
+        }
+        None => out.push_str(id),
+    });
+}
+
+impl IntoIterator for Errors {
+    type Item = (ErrorKey, Arc<dyn Error + Send + Sync>);
+    type IntoIter = IntoIter;
+
+    #[inline(always)]
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+/// Combines all the errors into a single [`io::Error`](std::io::Error), e.g.
+/// to return them from a server handler that returns an `io::Result`.
+///
+/// The errors have nothing to do with I/O, so the result always has the kind
+/// [`io::ErrorKind::Other`](std::io::ErrorKind::Other). Its message lists
+/// each key and error on a line of its own, in the order of [`Errors::iter`].
+impl From<Errors> for std::io::Error {
+    fn from(errors: Errors) -> Self {
+        let mut message = match errors.len() {
+            1 => "1 error:".to_string(),
+            len => format!("{len} errors:"),
+        };
+        for (key, error) in errors.iter() {
+            message.push_str(&format!("\n  {}: {error}", key.as_str()));
+        }
+        std::io::Error::other(message)
+    }
+}
+
+/// An owning iterator over all the errors contained in the [Errors] struct.
+#[repr(transparent)]
+pub struct IntoIter(indexmap::map::IntoIter<ErrorKey, ErrorEntry>);
+
+impl Iterator for IntoIter {
+    type Item = (ErrorKey, Arc<dyn Error + Send + Sync>);
+
+    #[inline(always)]
+    fn next(
+        &mut self,
+    ) -> std::option::Option<<Self as std::iter::Iterator>::Item> {
+        self.0.next().map(|(key, entry)| (key, entry.error))
+    }
+}
+
+/// An iterator over all the errors contained in the [Errors] struct.
+#[repr(transparent)]
+pub struct Iter<'a>(indexmap::map::Iter<'a, ErrorKey, ErrorEntry>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>);
+
+    #[inline(always)]
+    fn next(
+        &mut self,
+    ) -> std::option::Option<<Self as std::iter::Iterator>::Item> {
+        self.0.next().map(|(key, entry)| (key, &entry.error))
+    }
+}
+
+/// A view into a single entry of the [Errors] struct, which may either be
+/// vacant or occupied. Returned by [`Errors::entry`].
+pub struct Entry<'a> {
+    errors: &'a mut Errors,
+    key: ErrorKey,
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of this entry.
+    #[inline(always)]
+    pub fn key(&self) -> &ErrorKey {
+        &self.key
+    }
+
+    /// Inserts `error` if the entry is vacant, and returns a mutable
+    /// reference to the stored error.
+    #[inline(always)]
+    pub fn or_insert<E>(self, error: E) -> &'a mut Arc<dyn Error + Send + Sync>
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.or_insert_with(|| error)
+    }
+
+    /// Inserts the error returned by `f` if the entry is vacant, and returns
+    /// a mutable reference to the stored error. `f` is only called if the
+    /// entry is vacant.
+    pub fn or_insert_with<E, F>(
+        self,
+        f: F,
+    ) -> &'a mut Arc<dyn Error + Send + Sync>
+    where
+        E: Error + Send + Sync + 'static,
+        F: FnOnce() -> E,
+    {
+        use indexmap::map::Entry as MapEntry;
+
+        let Entry { errors, key } = self;
+        // either the caller gets mutable access to the error, or it's new
+        errors.version = errors.version.wrapping_add(1);
+        let slot = reserved_slot(&errors.reserved, &key);
+        let (index, inserted) = match errors.entries.entry(key) {
+            MapEntry::Occupied(entry) => (entry.index(), false),
+            MapEntry::Vacant(entry) => {
+                let index = entry.index();
+                let mut new = ErrorEntry::typed(f());
+                new.version = errors.version;
+                entry.insert(new);
+                (index, true)
+            }
+        };
+        // like `insert_entry`, place a new error for a reserved key after
+        // the errors for the keys reserved before it
+        let index = if inserted && slot < usize::MAX {
+            let target = errors
+                .entries
+                .keys()
+                .take_while(|key| reserved_slot(&errors.reserved, key) < slot)
+                .count();
+            errors.entries.move_index(index, target);
+            target
+        } else {
+            index
+        };
+        // looking up by index doesn't hash the key again
+        let (_, entry) = errors
+            .entries
+            .get_index_mut(index)
+            .expect("index of the entry");
+        entry.error_mut()
+    }
+
+    /// Modifies the stored error in place if the entry is occupied.
+    pub fn and_modify<F>(self, f: F) -> Self
+    where
+        F: FnOnce(&mut Arc<dyn Error + Send + Sync>),
+    {
+        if let Some(entry) = self.errors.entries.get_mut(&self.key) {
+            f(entry.error_mut());
+            self.errors.version = self.errors.version.wrapping_add(1);
+        }
+        self
+    }
+}
+
+/// An error with a description of what was being done when it occurred, as
+/// added by [`Errors::add_context`] and [`Errors::insert_with_context`].
+///
+/// It is displayed as the context followed by the original error, e.g.
+/// `while loading the dashboard: connection refused`, and the original error
+/// is its [`source`](Error::source).
+#[derive(Debug, Clone)]
+pub struct ContextError {
+    context: String,
+    source: Arc<dyn Error + Send + Sync>,
+}
+
+impl ContextError {
+    /// Wraps `source` with the given context.
+    pub fn new(
+        context: impl Into<String>,
+        source: Arc<dyn Error + Send + Sync>,
+    ) -> Self {
+        Self {
+            context: context.into(),
+            source,
+        }
+    }
+
+    /// The context the error was wrapped with.
+    pub fn context(&self) -> &str {
+        &self.context
+    }
+}
+
+impl fmt::Display for ContextError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.context, self.source)
+    }
+}
+
+impl Error for ContextError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.source)
+    }
+}
+
+/// An iterator over an error and its chain of [`source`](Error::source)s,
+/// starting with the error itself.
+#[derive(Clone)]
+#[repr(transparent)]
+pub struct ErrorChain<'a>(Option<&'a (dyn Error + 'static)>);
+
+impl<'a> ErrorChain<'a> {
+    /// Creates an iterator over `error` and all of its sources.
+    #[inline(always)]
+    pub fn new(error: &'a (dyn Error + 'static)) -> Self {
+        Self(Some(error))
+    }
+}
+
+impl<'a> Iterator for ErrorChain<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    #[inline(always)]
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.0?;
+        self.0 = current.source();
+        Some(current)
+    }
+}
+
+/// Renders the `Ok` value, clearing any error rendered at the same location
+/// before, or reports the `Err` to the nearest `<ErrorBoundary/>`.
+///
+/// Since `Option<T>` is a view as well, this covers resources of the shape
+/// `Result<Option<T>, E>` too: `Ok(None)` renders nothing, but still clears
+/// the error.
+///
+/// An error inserted at the same location with [`Errors::insert_sticky`] is
+/// not cleared, neither by an `Ok` value nor when the view is disposed.
+impl<T, E> IntoView for Result<T, E>
+where
+    T: IntoView + 'static,
+    E: Error + Send + Sync + 'static,
+{
+    fn into_view(self, cx: leptos_reactive::Scope) -> crate::View {
+        match self {
+            Ok(stuff) => {
+                // only derive the key if there is anywhere to clear it from
+                if !has_error_handler(cx) {
+                    return stuff.into_view(cx);
+                }
+                // the key is written to a reused buffer, which is only
+                // taken by one `Ok` at a time unless clearing the error
+                // renders another
+                let mut base = KEY_BUFFER.with(|buffer| buffer.take());
+                base.clear();
+                write_base_key(cx, &mut base);
+                match nesting_depth(&base) {
+                    0 => clear_error(cx, &base),
+                    depth => clear_error(cx, nested_key(&base, depth).as_str()),
+                }
+
+                // a `Result` rendered as `stuff` is at the same location, and
+                // must not reuse this one's key
+                RENDERING_OK.with(|keys| keys.borrow_mut().push(&base));
+                KEY_BUFFER.with(|buffer| buffer.set(base));
+                let view = stuff.into_view(cx);
+                RENDERING_OK.with(|keys| keys.borrow_mut().pop());
+                view
+            }
+            Err(error) => {
+                report_error(cx, derive_key(cx), error);
+                ().into_view(cx)
+            }
+        }
+    }
+}
+
+/// Renders a `Result` with a fallback of its own for the `Err` case, rather
+/// than relying on an `<ErrorBoundary/>`.
+///
+/// ```
+/// # use leptos_dom::{IntoView, ResultViewExt};
+/// # use leptos_reactive::*;
+/// # create_scope(create_runtime(), |cx| {
+/// let age: Result<u8, _> = "-1".parse::<u8>();
+/// let view = age.into_view_or(cx, || "Please enter a valid age.");
+/// # }).dispose();
+/// ```
+pub trait ResultViewExt {
+    /// Renders the `Ok` value like the `IntoView` implementation of `Result`
+    /// does, and the view returned by `fallback` in place of an `Err`.
+    ///
+    /// The error is still reported to the nearest `<ErrorBoundary/>`, if
+    /// there is one, but no warning is logged if there isn't, since the
+    /// error has been handled by the fallback.
+    fn into_view_or<F, V>(self, cx: Scope, fallback: F) -> crate::View
+    where
+        F: FnOnce() -> V,
+        V: IntoView;
+}
+
+impl<T, E> ResultViewExt for Result<T, E>
+where
+    T: IntoView + 'static,
+    E: Error + Send + Sync + 'static,
+{
+    fn into_view_or<F, V>(self, cx: Scope, fallback: F) -> crate::View
+    where
+        F: FnOnce() -> V,
+        V: IntoView,
+    {
+        match self {
+            Ok(stuff) => Ok::<T, E>(stuff).into_view(cx),
+            Err(error) => {
+                if has_error_handler(cx) {
+                    report_error(cx, derive_key(cx), error);
+                }
+                fallback().into_view(cx)
+            }
+        }
+    }
+}
+
+/// Renders a sequence of `Result`s, reporting each `Err` separately to the
+/// nearest `<ErrorBoundary/>`, without collecting the results first.
+///
+/// Each item's key is the key the sequence would get as a single `Result`
+/// view, followed by `#` and the item's position, e.g. `0-1-#3`, so an item
+/// keeps its key as long as it keeps its position. When the view is
+/// re-rendered, the errors of items that are now `Ok`, or no longer exist,
+/// are removed like those of any other `Result` view.
+pub fn render_results<T, E>(
+    cx: Scope,
+    results: impl IntoIterator<Item = Result<T, E>>,
+) -> crate::View
+where
+    T: IntoView + 'static,
+    E: Error + Send + Sync + 'static,
+{
+    let base = derive_key(cx);
+    let views = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| {
+            let key = ErrorKey(format!("{}#{index}", base.as_str()).into());
+            match result {
+                Ok(stuff) => {
+                    clear_error(cx, key.as_str());
+                    stuff.into_view(cx)
+                }
+                Err(error) => {
+                    report_error(cx, key, error);
+                    ().into_view(cx)
+                }
+            }
+        })
+        .collect();
+    crate::Fragment::new(views).into_view(cx)
+}
+
+/// Renders the result of `f` like a `Result` view, and returns it along with
+/// a function that retries `f`, e.g. from the `on:click` handler of a retry
+/// button, without resetting the whole `<ErrorBoundary/>`.
+///
+/// The error is reported under a key derived once, where this is called, so
+/// every attempt reports to the same key: a retry that fails again replaces
+/// the previous error, and one that succeeds clears it and renders the new
+/// content in place of the old. Only this view is re-rendered.
+///
+/// `f` also runs again whenever a signal it reads changes, like any other
+/// reactive view.
+pub fn retryable_view<V, E>(
+    cx: Scope,
+    f: impl Fn() -> Result<V, E> + 'static,
+) -> (impl IntoView, impl Fn() + Copy + 'static)
+where
+    V: IntoView,
+    E: Error + Send + Sync + 'static,
+{
+    let key = derive_key(cx);
+    let attempt = create_rw_signal(cx, 0_usize);
+    let content = create_rw_signal(cx, None::<crate::View>);
+
+    create_isomorphic_effect(cx, move |prev: Option<ScopeDisposer>| {
+        // disposing the previous attempt's scope cleans up its error
+        if let Some(disposer) = prev {
+            disposer.dispose();
+        }
+        attempt.with(|_| {});
+        let (view, disposer) = cx.run_child_scope(|cx| match f() {
+            Ok(stuff) => {
+                clear_error(cx, key.as_str());
+                stuff.into_view(cx)
+            }
+            Err(error) => {
+                report_error(cx, key.clone(), error);
+                ().into_view(cx)
+            }
+        });
+        content.set(Some(view));
+        disposer
+    });
+
+    let retry = move || attempt.update(|attempt| *attempt += 1);
+    (move || content.get(), retry)
+}
+
+/// Schedules `task` to run in a microtask, with the following fallbacks:
+/// 1. `queueMicrotask`, if the host provides it
+/// 2. `Promise.resolve().then(task)`, which also runs `task` as a microtask and
+///    is available in any JavaScript host, including non-browser ones that
+///    don't provide `queueMicrotask`
+#[cfg(all(target_arch = "wasm32", feature = "web"))]
+fn queue_cleanup(task: impl FnOnce() + 'static) {
+    use wasm_bindgen::{closure::Closure, JsCast, JsValue, UnwrapThrowExt};
+
+    thread_local! {
+        static HAS_QUEUE_MICROTASK: bool = js_sys::Reflect::get(
+            &js_sys::global(),
+            &JsValue::from_str("queueMicrotask"),
+        )
+        .map(|f| f.is_function())
+        .unwrap_or(false);
+    }
+
+    if HAS_QUEUE_MICROTASK.with(|has| *has) {
+        leptos_reactive::queue_microtask(task);
+    } else {
+        // `Promise.prototype.then` is called directly, rather than through
+        // `Promise::then`, so that the closure frees itself once it has run
+        let promise = js_sys::Promise::resolve(&JsValue::UNDEFINED);
+        let then: js_sys::Function =
+            js_sys::Reflect::get(&promise, &JsValue::from_str("then"))
+                .unwrap_throw()
+                .unchecked_into();
+        _ = then.call1(&promise, &Closure::once_into_js(task));
+    }
+}
+
+/// Periodically removes the errors in the nearest `<ErrorBoundary/>` that
+/// were inserted more than `max_age` ago, checking every `interval`.
+///
+/// Errors are normally removed when the view that rendered them is cleaned
+/// up. This is a safety net for long-lived client sessions in which errors
+/// can accumulate anyway, for example because of a key mismatch.
+///
+/// The timer is cancelled when `cx` is disposed. This does nothing on the
+/// server, or if there is no `<ErrorBoundary/>`.
+pub fn start_error_gc(cx: Scope, interval: Duration, max_age: Duration) {
+    cfg_if! {
+      if #[cfg(all(target_arch = "wasm32", feature = "web"))] {
+        use leptos_reactive::on_cleanup;
+
+        let errors = match use_context::<RwSignal<Errors>>(cx) {
+            Some(errors) => errors,
+            None => return,
+        };
+        let handle = crate::helpers::set_interval_with_handle(
+            move || {
+                let now = now();
+                let has_stale = errors.with_untracked(|errors| {
+                    errors
+                        .entries
+                        .values()
+                        .any(|entry| entry.is_older_than(max_age, now))
+                });
+                if has_stale {
+                    errors.update(|errors| {
+                        errors.remove_older_than(max_age, now);
+                    });
+                }
+            },
+            interval,
+        );
+        match handle {
+            Ok(handle) => on_cleanup(cx, move || handle.clear()),
+            Err(e) => {
+                crate::debug_warn!("could not start error GC: {e:?}");
+            }
+        }
+      } else {
+        _ = (cx, interval, max_age);
+      }
+    }
+}
+
+/// Runs `f` with mutable access to the errors in `errors`, with
+/// [`Errors::modify`], and only notifies the subscribers of the signal if
+/// anything changed. This avoids re-rendering the fallback for updates that
+/// turn out to be no-ops.
+///
+/// Returns `None` if the signal has been disposed.
+pub fn modify_errors<R>(
+    errors: RwSignal<Errors>,
+    f: impl FnOnce(&mut Errors) -> R,
+) -> Option<R> {
+    let (result, changed) =
+        errors.try_update_untracked(|errors| errors.modify(f))?;
+    if changed {
+        errors.update(|_| {});
+    }
+    Some(result)
+}
+
+/// Clears all the errors in the nearest `<ErrorBoundary/>` whenever `trigger`
+/// changes, e.g. to drop the errors of the previous page when the current
+/// route changes.
+///
+/// The subscription to `trigger` belongs to `cx`, and ends when `cx` is
+/// disposed. This does nothing if there is no `<ErrorBoundary/>`.
+pub fn clear_errors_on<T>(cx: Scope, trigger: Signal<T>) {
+    let errors = match use_context::<RwSignal<Errors>>(cx) {
+        Some(errors) => errors,
+        None => return,
+    };
+    create_isomorphic_effect(cx, move |prev: Option<()>| {
+        trigger.with(|_| {});
+        // the first run only subscribes to the trigger
+        if prev.is_some() && !errors.with_untracked(Errors::is_empty) {
+            errors.update(|errors| errors.entries.clear());
+        }
+    });
+}
+
+/// Calls `record` with the number of errors in the nearest `<ErrorBoundary/>`
+/// right away, and again whenever that number changes, e.g. to record how
+/// many errors a server render produced.
+///
+/// On the server, the last call during a render is the number of errors the
+/// page was rendered with. To export it to Prometheus, `record` can
+/// [`observe`](https://docs.rs/prometheus/latest/prometheus/struct.Histogram.html#method.observe)
+/// it in a histogram of errors per render, or set a gauge with it; to count
+/// the errors themselves, add the difference to the previous call to a
+/// counter.
+///
+/// Like [`clear_errors_on`], the subscription belongs to `cx`, and this does
+/// nothing if there is no `<ErrorBoundary/>`.
+pub fn provide_error_metrics(cx: Scope, record: impl Fn(usize) + 'static) {
+    let errors = match use_context::<RwSignal<Errors>>(cx) {
+        Some(errors) => errors,
+        None => return,
+    };
+    create_isomorphic_effect(cx, move |prev: Option<usize>| {
+        let count = errors.with(Errors::len);
+        if prev != Some(count) {
+            record(count);
+        }
+        count
+    });
+}
+
+impl Errors {
+    /// Creates an empty set of errors whose map uses the given hasher for
+    /// lookups. [`Errors::default`] uses [`ErrorHasher::Std`].
+    ///
+    /// The hasher is kept by clones and by the sets of errors derived from
+    /// this one, e.g. with [`Errors::split`] or [`Errors::map_errors`].
+    ///
+    /// ```
+    /// # use leptos_dom::{ErrorHasher, ErrorKey, Errors};
+    /// # use std::fmt;
+    /// # #[derive(Debug)]
+    /// # struct LookupError;
+    /// # impl fmt::Display for LookupError {
+    /// #     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    /// #         f.write_str("lookup failed")
+    /// #     }
+    /// # }
+    /// # impl std::error::Error for LookupError {}
+    /// let mut errors = Errors::with_hasher(ErrorHasher::Fx);
+    /// for i in 0..10_000 {
+    ///     errors.insert(ErrorKey::from(format!("row:{i}")), LookupError);
+    /// }
+    /// assert_eq!(errors.len(), 10_000);
+    /// ```
+    pub fn with_hasher(hasher: ErrorHasher) -> Self {
+        Self {
+            entries: IndexMap::with_hasher(hasher.into()),
+            version: 0,
+            reserved: Vec::new(),
+        }
+    }
+
+    fn insert_entry(&mut self, key: ErrorKey, mut entry: ErrorEntry) {
+        self.version = self.version.wrapping_add(1);
+        entry.version = self.version;
+        let slot = reserved_slot(&self.reserved, &key);
+        let (index, replaced) = self.entries.insert_full(key, entry);
+        // a replaced error keeps its position, but a new one for a reserved
+        // key goes after the errors for the keys reserved before it
+        if slot < usize::MAX && replaced.is_none() {
+            let target = self
+                .entries
+                .keys()
+                .take_while(|key| reserved_slot(&self.reserved, key) < slot)
+                .count();
+            self.entries.move_index(index, target);
+        }
+    }
+
+    /// Reserves slots for errors with the given keys, e.g. one for each field
+    /// of a form, so that they are iterated and rendered in this order
+    /// whether or not other errors were inserted before them. Keys that are
+    /// already reserved keep their slot.
+    ///
+    /// Reserved keys without an error are only placeholders: they are not
+    /// counted by [`Errors::is_empty`] or [`Errors::len`], and are not
+    /// iterated over.
+    pub fn reserve_keys(&mut self, keys: impl IntoIterator<Item = ErrorKey>) {
+        for key in keys {
+            if !self.reserved.contains(&key) {
+                self.reserved.push(key);
+            }
+        }
+        let reserved = &self.reserved;
+        // stable, so that the errors for other keys stay in insertion order
+        self.entries.sort_by(|a, _, b, _| {
+            reserved_slot(reserved, a).cmp(&reserved_slot(reserved, b))
+        });
+    }
+
+    /// Returns the number of errors, not counting the placeholders for keys
+    /// reserved with [`Errors::reserve_keys`].
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Runs `f` with mutable access to the errors, and returns its result
+    /// along with whether it changed anything. Inserting an error, or getting
+    /// mutable access to one through an [`Errors::entry`], counts as a change
+    /// even if the new error is identical, but reading errors or removing
+    /// ones that don't exist does not.
+    ///
+    /// See [`modify_errors`] to only notify the subscribers of a signal if
+    /// anything changed.
+    pub fn modify<R>(&mut self, f: impl FnOnce(&mut Errors) -> R) -> (R, bool) {
+        let (len, version) = (self.entries.len(), self.version);
+        let result = f(self);
+        (result, self.entries.len() != len || self.version != version)
+    }
+
+    /// Returns the errors that were inserted after the errors were at
+    /// `version`, along with the current version to pass to the next call.
+    /// Passing `0` returns every error.
+    ///
+    /// This lets a fallback handle only the errors that are new since it last
+    /// rendered, e.g. to animate them in. Errors that were replaced count as
+    /// new, but ones that were only modified through an [`Errors::entry`] do
+    /// not.
+    pub fn since(&self, version: u64) -> (Vec<ErrorRef<'_>>, u64) {
+        let added = self
+            .entries
+            .iter()
+            .filter(|(_, entry)| entry.version > version)
+            .map(|(key, entry)| (key, &entry.error))
+            .collect();
+        (added, self.version)
+    }
+
+    /// Returns `true` if there are no errors, even if some keys have been
+    /// reserved with [`Errors::reserve_keys`].
+    ///
+    /// This counts errors of every [ErrorSeverity], including warnings, which
+    /// is what `<ErrorBoundary/>` uses to decide whether to show its fallback.
+    /// Use [`Errors::has_blocking`] to ignore warnings instead.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns `true` if there are any errors more severe than
+    /// [`ErrorSeverity::Warning`]. Warnings can still be displayed, but
+    /// should not block progress, e.g. the submission of a form.
+    pub fn has_blocking(&self) -> bool {
+        self.entries
+            .values()
+            .any(|entry| entry.severity > ErrorSeverity::Warning)
+    }
+
+    /// Returns `true` if `other` has an error under each key of this set, with
+    /// the same message as returned by [`Errors::user_message`], e.g. to
+    /// assert in a test that a component produced at least these errors.
+    ///
+    /// The types, severities and order of the errors are not compared.
+    pub fn is_subset_of(&self, other: &Errors) -> bool {
+        self.entries.iter().all(|(key, entry)| {
+            matches!(
+                other.entries.get(key),
+                Some(other) if other.message() == entry.message()
+            )
+        })
+    }
+
+    /// Returns `true` if there is an error under each of the given keys,
+    /// whatever the errors are.
+    pub fn contains_all(&self, keys: &[ErrorKey]) -> bool {
+        keys.iter().all(|key| self.entries.contains_key(key))
+    }
+
+    /// Returns a hash of the current errors, e.g. to skip re-rendering an
+    /// expensive fallback when it hasn't changed.
+    ///
+    /// The hash covers each key and the [`Display`](fmt::Display) output of
+    /// its error, even if the error has a [UserFacing] message that hides
+    /// the difference. Two sets with the same keys and messages hash the same,
+    /// whatever order the errors were inserted in, and whatever their types,
+    /// severities or metadata. It is stable within a build, but shouldn't be
+    /// persisted, as the hashing algorithm may change between releases.
+    pub fn state_hash(&self) -> u64 {
+        use std::{
+            collections::hash_map::DefaultHasher,
+            hash::{Hash, Hasher},
+        };
+
+        let mut entries = self.entries.iter().collect::<Vec<_>>();
+        entries.sort_unstable_by_key(|(key, _)| *key);
+
+        let mut hasher = DefaultHasher::new();
+        for (key, entry) in entries {
+            key.hash(&mut hasher);
+            entry.display_message().hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns how many errors there are of each [ErrorSeverity], e.g. for a
+    /// dashboard summary. Severities without any errors are left out.
+    pub fn count_by_severity(&self) -> HashMap<ErrorSeverity, usize> {
+        let mut counts = HashMap::new();
+        for entry in self.entries.values() {
+            *counts.entry(entry.severity).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Panics if there are any errors, with the key and message of each of
+    /// them in the panic message, sorted by key. This is meant for tests that
+    /// should fail if rendering produced an error.
+    #[track_caller]
+    pub fn assert_empty(&self) {
+        if self.is_empty() {
+            return;
+        }
+        let mut errors = self.iter().collect::<Vec<_>>();
+        errors.sort_by_key(|(key, _)| *key);
+        let mut message =
+            format!("expected no errors, found {}:", errors.len());
+        for (key, error) in errors {
+            message.push_str(&format!("\n  {}: {error}", key.as_str()));
+        }
+        panic!("{message}");
+    }
+
+    /// If this contains exactly one error and it is of type `E`, returns the
+    /// owned error. Otherwise, returns the [Errors] back unchanged.
+    ///
+    /// This fails if:
+    /// - there are no errors, or more than one
+    /// - the error is not of type `E`
+    /// - the error's [Arc] is shared, for example with a clone of this set
+    /// - the error was not inserted with its concrete type, for example one
+    ///   replaced through an [Entry]
+    pub fn into_single<E>(mut self) -> Result<E, Errors>
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        if self.entries.len() != 1 {
+            return Err(self);
+        }
+        let key = self.entries.keys().next().cloned().unwrap_or_default();
+        let entry = self
+            .entries
+            .shift_remove(&key)
+            .expect("checked length above");
+        entry.try_into_inner().map_err(|entry| {
+            self.entries.insert(key, entry);
+            self
+        })
+    }
+
+    /// Add an error to Errors that will be processed by `<ErrorBoundary/>`
+    pub fn insert<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.insert_entry(key, ErrorEntry::typed(error));
+    }
+
+    /// Add an error like [`Errors::insert`], and return the number of errors
+    /// afterwards, e.g. to stop validating after too many failures.
+    ///
+    /// ```
+    /// # use leptos_dom::{ErrorKey, Errors};
+    /// let mut errors = Errors::default();
+    /// for (line, input) in ["1", "x", "2", "y", "z"].into_iter().enumerate() {
+    ///     if let Err(error) = input.parse::<u8>() {
+    ///         let key = ErrorKey::from(format!("line {line}"));
+    ///         if errors.insert_counting(key, error) >= 2 {
+    ///             break;
+    ///         }
+    ///     }
+    /// }
+    /// assert_eq!(errors.len(), 2);
+    /// ```
+    pub fn insert_counting<E>(&mut self, key: ErrorKey, error: E) -> usize
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.insert(key, error);
+        self.entries.len()
+    }
+
+    /// Add the error returned by `f`, unless there already is an error under
+    /// `key`, in which case `f` is not called at all. Returns `true` if the
+    /// error was inserted.
+    ///
+    /// This avoids building an expensive error, e.g. one that formats a large
+    /// payload, only to find a previous one for the same problem. To replace
+    /// the existing error instead, use [`Errors::insert`] with the result of
+    /// `f`.
+    pub fn insert_with<E>(
+        &mut self,
+        key: ErrorKey,
+        f: impl FnOnce() -> E,
+    ) -> bool
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        if self.entries.contains_key(&key) {
+            return false;
+        }
+        self.insert(key, f());
+        true
+    }
+
+    /// Add an error with the given [ErrorSeverity]. Errors inserted by other
+    /// methods have a severity of [`ErrorSeverity::Error`].
+    pub fn insert_with_severity<E>(
+        &mut self,
+        key: ErrorKey,
+        error: E,
+        severity: ErrorSeverity,
+    ) where
+        E: Error + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.severity = severity;
+        self.insert_entry(key, entry);
+    }
+
+    /// Add an error that provides a [UserFacing] message, which will be
+    /// preferred over its [`Display`](fmt::Display) implementation by
+    /// [`Errors::user_message`] and the built-in error views.
+    pub fn insert_user_facing<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + UserFacing + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.user_message = Some(downcast_user_message::<E>);
+        self.insert_entry(key, entry);
+    }
+
+    /// Add an error with the given [ErrorKind]. Errors inserted by other
+    /// methods are [`ErrorKind::Permanent`], unless inserted with
+    /// [`Errors::insert_retryable`].
+    pub fn insert_with_kind<E>(
+        &mut self,
+        key: ErrorKey,
+        error: E,
+        kind: ErrorKind,
+    ) where
+        E: Error + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.kind = kind;
+        self.insert_entry(key, entry);
+    }
+
+    /// Add an error that stays until it is removed deliberately, e.g. with
+    /// [`Errors::clear_sticky`], for audit-style interfaces.
+    ///
+    /// `Result` views normally remove the error under their key when they
+    /// become `Ok` or are disposed. A sticky error under that key is left in
+    /// place by both. It is still replaced if the view renders a new `Err`,
+    /// which is then cleaned up as usual.
+    pub fn insert_sticky<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.sticky = true;
+        self.insert_entry(key, entry);
+    }
+
+    /// Removes all the errors inserted with [`Errors::insert_sticky`].
+    pub fn clear_sticky(&mut self) {
+        self.entries.retain(|_, entry| !entry.sticky);
+    }
+
+    /// Add an error whose [ErrorKind] is given by its [Retryable]
+    /// implementation.
+    pub fn insert_retryable<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + Retryable + Send + Sync + 'static,
+    {
+        let kind = if error.is_transient() {
+            ErrorKind::Transient
+        } else {
+            ErrorKind::Permanent
+        };
+        self.insert_with_kind(key, error, kind);
+    }
+
+    /// Add an error wrapped in a [ContextError] with the given context.
+    pub fn insert_with_context<E>(
+        &mut self,
+        key: ErrorKey,
+        error: E,
+        context: impl Into<String>,
+    ) where
+        E: Error + Send + Sync + 'static,
+    {
+        self.insert(key, ContextError::new(context, Arc::new(error)));
+    }
+
+    /// Add an error whose [`Display`](fmt::Display) output is computed once,
+    /// now, and stored along with it. This trades memory for rendering speed
+    /// when formatting the error is expensive, as fallbacks may format it on
+    /// every render: use [`Errors::cached_message`] to get the stored message.
+    pub fn insert_cached<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.cached_message = Some(entry.error.to_string().into());
+        self.insert_entry(key, entry);
+    }
+
+    /// Adds a batch of errors that share the same context, e.g. the id of
+    /// the request that produced them. The metadata is allocated once and
+    /// attached to every error of the batch; see [`Errors::metadata`].
+    pub fn insert_batch<E>(
+        &mut self,
+        entries: impl IntoIterator<Item = (ErrorKey, E)>,
+        shared_meta: HashMap<String, String>,
+    ) where
+        E: Error + Send + Sync + 'static,
+    {
+        let metadata = Arc::new(shared_meta);
+        for (key, error) in entries {
+            let mut entry = ErrorEntry::typed(error);
+            entry.metadata = Some(Arc::clone(&metadata));
+            self.insert_entry(key, entry);
+        }
+    }
+
+    /// Inserts the error under `key` if `result` is an `Err`, and returns the
+    /// `Ok` value otherwise. This is useful to collect errors outside of
+    /// views, e.g. when validating input on the server.
+    pub fn insert_result<T, E>(
+        &mut self,
+        key: ErrorKey,
+        result: Result<T, E>,
+    ) -> Option<T>
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        match result {
+            Ok(value) => Some(value),
+            Err(error) => {
+                self.insert(key, error);
+                None
+            }
+        }
+    }
+
+    /// Add an error with the default key for errors outside the reactive system
+    ///
+    /// There is only one default key, so this replaces any error previously
+    /// inserted with it. Use [`Errors::default_error`] to get it back.
+    pub fn insert_with_default_key<E>(&mut self, error: E)
+    where
+        E: Error + Send + Sync + 'static,
+    {
+        self.insert_entry(Default::default(), ErrorEntry::typed(error));
+    }
+
+    /// Returns the error inserted with [`Errors::insert_with_default_key`],
+    /// if any.
+    ///
+    /// ```
+    /// # use leptos_dom::Errors;
+    /// let mut errors = Errors::default();
+    /// errors.insert_with_default_key("x".parse::<u8>().unwrap_err());
+    ///
+    /// let error = errors.default_error().unwrap();
+    /// assert_eq!(error.to_string(), "invalid digit found in string");
+    /// ```
+    pub fn default_error(&self) -> Option<&Arc<dyn Error + Send + Sync>> {
+        self.entries
+            .get(&ErrorKey::default())
+            .map(|entry| &entry.error)
+    }
+
+    /// Remove an error to Errors that will be processed by `<ErrorBoundary/>`
+    pub fn remove(
+        &mut self,
+        key: &ErrorKey,
+    ) -> Option<Arc<dyn Error + Send + Sync>> {
+        self.entries.shift_remove(key).map(|entry| entry.error)
+    }
+
+    /// Removes the error with the given key and returns it, like
+    /// [`Errors::remove`], but accepts anything that converts into an
+    /// [ErrorKey], such as a string literal or an [ErrorKeyEnum] variant.
+    ///
+    /// ```
+    /// # use leptos_dom::{ErrorKey, Errors};
+    /// # use leptos_reactive::*;
+    /// # use std::{error::Error, sync::Arc};
+    /// # create_scope(create_runtime(), |cx| {
+    /// let errors = create_rw_signal(cx, Errors::default());
+    /// let modal = create_rw_signal(cx, None::<Arc<dyn Error + Send + Sync>>);
+    /// errors.update(|errors| {
+    ///     errors.insert(ErrorKey::from("payment"), std::fmt::Error)
+    /// });
+    ///
+    /// // show the error in a modal rather than in the inline list
+    /// modal.set(
+    ///     errors
+    ///         .try_update(|errors| errors.take_by("payment"))
+    ///         .flatten(),
+    /// );
+    /// assert!(modal.with(Option::is_some));
+    /// assert!(errors.with(Errors::is_empty));
+    /// # }).dispose();
+    /// ```
+    pub fn take_by<K>(&mut self, key: K) -> Option<Arc<dyn Error + Send + Sync>>
+    where
+        K: Into<ErrorKey>,
+    {
+        self.remove(&key.into())
+    }
+
+    /// Removes the error with the given id, as returned by
+    /// [`Errors::iter_with_ids`], e.g. when the user dismisses it.
+    ///
+    /// Keys derived from the view tree can refer to a different error after
+    /// a re-render, but an id only ever refers to the error it was assigned
+    /// to, so this never removes another error. Returns `None` if that error
+    /// has already been removed or replaced.
+    pub fn dismiss(&mut self, id: u64) -> Option<Arc<dyn Error + Send + Sync>> {
+        let index = self.entries.values().position(|entry| entry.id == id)?;
+        self.entries
+            .shift_remove_index(index)
+            .map(|(_, entry)| entry.error)
+    }
+
+    /// Returns a message for the error with the given key that is suitable
+    /// for showing to users: the [UserFacing] message if the error was
+    /// inserted with [`Errors::insert_user_facing`], and its
+    /// [`Display`](fmt::Display) implementation otherwise, which is not run
+    /// again for errors inserted with [`Errors::insert_cached`].
+    ///
+    /// Returns `None` if there is no error with this key.
+    pub fn user_message(&self, key: &ErrorKey) -> Option<String> {
+        self.entries.get(key).map(ErrorEntry::message)
+    }
+
+    /// Returns the message that was stored for the error with the given key
+    /// by [`Errors::insert_cached`], without formatting the error again.
+    ///
+    /// Returns `None` if there is no error with this key, or if it was
+    /// inserted by another method.
+    pub fn cached_message(&self, key: &ErrorKey) -> Option<&str> {
+        self.entries.get(key)?.cached_message.as_deref()
+    }
+
+    /// Clones every error of type `E` into a [Vec], in insertion order, like
+    /// [`Errors::iter`]. Errors of other types are skipped.
+    pub fn collect_type<E>(&self) -> Vec<E>
+    where
+        E: Error + Clone + 'static,
+    {
+        self.entries
+            .values()
+            .filter_map(|entry| entry.error.downcast_ref::<E>())
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the first error, in the order of [`Errors::iter`], for which
+    /// `f` returns `true`, e.g. to pick a representative error to display.
+    pub fn find<F>(
+        &self,
+        mut f: F,
+    ) -> Option<(&ErrorKey, &Arc<dyn Error + Send + Sync>)>
+    where
+        F: FnMut(&ErrorKey, &Arc<dyn Error + Send + Sync>) -> bool,
+    {
+        self.iter().find(|(key, error)| f(key, error))
+    }
+
+    /// Returns `true` if any error is of type `E`.
+    pub fn contains_type<E>(&self) -> bool
+    where
+        E: Error + 'static,
+    {
+        self.entries.values().any(|entry| entry.error.is::<E>())
+    }
+
+    /// Transforms every stored error with `f`, keeping its key and metadata.
+    ///
+    /// This can be used, for example, to redact sensitive information from
+    /// the errors before they are displayed or logged, or to wrap each error
+    /// with additional context.
+    pub fn map_errors<F>(self, f: F) -> Errors
+    where
+        F: Fn(Arc<dyn Error + Send + Sync>) -> Arc<dyn Error + Send + Sync>,
+    {
+        let mut entries = IndexMap::with_capacity_and_hasher(
+            self.entries.len(),
+            self.entries.hasher().clone(),
+        );
+        entries.extend(self.entries.into_iter().map(|(key, entry)| {
+            let entry = ErrorEntry {
+                error: f(entry.error),
+                any: None,
+                cached_message: None,
+                ..entry
+            };
+            (key, entry)
+        }));
+        Errors {
+            entries,
+            version: self.version,
+            reserved: self.reserved,
+        }
+    }
+
+    /// Splits the errors in one pass into those for which `f` returns `true`
+    /// and the rest, e.g. to show blocking errors inline and the others as
+    /// toasts.
+    ///
+    /// Both halves keep the order of [`Errors::iter`], and each error keeps
+    /// its key, severity and metadata.
+    pub fn split<F>(self, mut f: F) -> (Errors, Errors)
+    where
+        F: FnMut(&ErrorKey, &Arc<dyn Error + Send + Sync>) -> bool,
+    {
+        let hasher = self.entries.hasher();
+        let mut matching = Errors {
+            entries: IndexMap::with_hasher(hasher.clone()),
+            version: self.version,
+            reserved: self.reserved.clone(),
+        };
+        let mut rest = Errors {
+            entries: IndexMap::with_hasher(hasher.clone()),
+            version: self.version,
+            reserved: self.reserved,
+        };
+        for (key, entry) in self.entries {
+            if f(&key, &entry.error) {
+                matching.entries.insert(key, entry);
+            } else {
+                rest.entries.insert(key, entry);
+            }
+        }
+        (matching, rest)
+    }
+
+    /// Wraps every stored error in a [ContextError] with the given context,
+    /// e.g. `"while loading the dashboard"`, when passing the errors on to
+    /// an outer boundary. Each error keeps its key and metadata, as well as
+    /// its [UserFacing] message, [HtmlError] markup and [DisplayHint], which
+    /// are taken from the wrapped error.
+    pub fn add_context(&mut self, context: impl Into<String>) {
+        let context = context.into();
+        for entry in self.entries.values_mut() {
+            let error = Arc::new(ContextError::new(
+                context.clone(),
+                Arc::clone(&entry.error),
+            ));
+            entry.error = error.clone();
+            entry.any = Some(error);
+            entry.cached_message = None;
+        }
+        self.version = self.version.wrapping_add(1);
+    }
+
+    /// Clones the set of errors. This allocates a new map with the same keys,
+    /// but the errors themselves are not copied: each value is an [Arc]
+    /// shared with the original set.
+    ///
+    /// This is exactly what [Clone] does, named to make the cost explicit.
+    #[inline(always)]
+    pub fn clone_shallow(&self) -> Errors {
+        self.clone()
+    }
+
+    /// Returns the [ErrorSeverity] of the error with the given key.
+    pub fn severity(&self, key: &ErrorKey) -> Option<ErrorSeverity> {
+        self.entries.get(key).map(|entry| entry.severity)
+    }
+
+    /// Returns the [ErrorKind] of the error with the given key.
+    pub fn kind(&self, key: &ErrorKey) -> Option<ErrorKind> {
+        self.entries.get(key).map(|entry| entry.kind)
+    }
+
+    /// An iterator over the [`ErrorKind::Transient`] errors, which are worth
+    /// retrying, in insertion order, like [`Errors::iter`].
+    pub fn transient(
+        &self,
+    ) -> impl Iterator<Item = (&ErrorKey, &Arc<dyn Error + Send + Sync>)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| entry.kind == ErrorKind::Transient)
+            .map(|(key, entry)| (key, &entry.error))
+    }
+
+    /// Marks the error with the given key as seen by the user, without
+    /// removing it, e.g. so that a badge only counts the errors that are
+    /// new to the user while the full list still shows every error. Returns
+    /// `false` if there is no error with this key.
+    ///
+    /// An error inserted under the same key later on is not acknowledged,
+    /// but one merged in with [`Errors::merge`] is.
+    pub fn acknowledge(&mut self, key: &ErrorKey) -> bool {
+        match self.entries.get_mut(key) {
+            Some(entry) => {
+                if !entry.acknowledged {
+                    entry.acknowledged = true;
+                    self.version = self.version.wrapping_add(1);
+                }
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns `true` if the error with the given key has been marked as
+    /// seen with [`Errors::acknowledge`].
+    pub fn is_acknowledged(&self, key: &ErrorKey) -> bool {
+        matches!(self.entries.get(key), Some(entry) if entry.acknowledged)
+    }
+
+    /// An iterator over the errors that have not been acknowledged, in the
+    /// same order as [`Errors::iter`].
+    pub fn iter_unacknowledged(
+        &self,
+    ) -> impl Iterator<Item = (&ErrorKey, &Arc<dyn Error + Send + Sync>)> {
+        self.entries
+            .iter()
+            .filter(|(_, entry)| !entry.acknowledged)
+            .map(|(key, entry)| (key, &entry.error))
+    }
+
+    /// Moves every error out of `other` into this set, replacing the errors
+    /// with the same keys. A replaced error that had been acknowledged stays
+    /// acknowledged, so that merging in a fresh set of errors, e.g. from the
+    /// server, doesn't mark the errors the user has already seen as new.
+    pub fn merge(&mut self, other: Errors) {
+        for (key, mut entry) in other.entries {
+            entry.acknowledged |= self.is_acknowledged(&key);
+            self.insert_entry(key, entry);
+        }
+    }
+
+    /// Returns when the error with the given key was inserted, as the time
+    /// since the Unix epoch.
+    pub fn inserted_at(&self, key: &ErrorKey) -> Option<Duration> {
+        self.entries.get(key).map(|entry| entry.inserted_at)
+    }
+
+    /// Returns the metadata attached to the error with the given key by
+    /// [`Errors::insert_batch`], if any.
+    pub fn metadata(
+        &self,
+        key: &ErrorKey,
+    ) -> Option<&Arc<HashMap<String, String>>> {
+        self.entries.get(key)?.metadata.as_ref()
+    }
+
+    /// Returns the spans that were entered when the error with the given key
+    /// was inserted.
+    ///
+    /// This is `None` on `wasm32`, or if no subscriber with the
+    /// [`ErrorLayer`](tracing_error::ErrorLayer) was installed at the time.
+    #[cfg(feature = "tracing-error")]
+    pub fn span_trace(
+        &self,
+        key: &ErrorKey,
+    ) -> Option<&tracing_error::SpanTrace> {
+        self.entries.get(key)?.span_trace.as_deref()
+    }
+
+    /// Removes every error that was inserted more than `max_age` before
+    /// `now`, which is the time since the Unix epoch. Returns the number of
+    /// errors that were removed.
+    ///
+    /// See [`start_error_gc`] to do this periodically.
+    pub fn remove_older_than(
+        &mut self,
+        max_age: Duration,
+        now: Duration,
+    ) -> usize {
+        let len = self.entries.len();
+        self.entries
+            .retain(|_, entry| !entry.is_older_than(max_age, now));
+        len - self.entries.len()
+    }
+
+    /// An iterator over the errors whose keys are namespaced by the given
+    /// [ErrorKeyPrefix], in insertion order, like [`Errors::iter`].
+    pub fn iter_with_prefix<'a>(
+        &'a self,
+        prefix: &'a ErrorKeyPrefix,
+    ) -> impl Iterator<Item = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>)>
+    {
+        self.iter().filter(move |(key, _)| prefix.contains(key))
+    }
+
+    /// Removes every error whose key is namespaced by the given
+    /// [ErrorKeyPrefix]. Returns the number of errors that were removed.
+    pub fn remove_with_prefix(&mut self, prefix: &ErrorKeyPrefix) -> usize {
+        let len = self.entries.len();
+        self.entries.retain(|key, _| !prefix.contains(key));
+        len - self.entries.len()
+    }
+
+    /// Gets the entry for the given key, for in-place modification or
+    /// conditional insertion without a second lookup.
+    ///
+    /// ```
+    /// # use leptos_dom::{ErrorKey, Errors};
+    /// #[derive(Debug)]
+    /// struct FormError(&'static str);
+    ///
+    /// impl std::fmt::Display for FormError {
+    ///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    ///         f.write_str(self.0)
+    ///     }
+    /// }
+    ///
+    /// impl std::error::Error for FormError {}
+    ///
+    /// let mut errors = Errors::default();
+    /// let key = ErrorKey::from("email");
+    ///
+    /// // only inserted because the key is vacant
+    /// errors
+    ///     .entry(key.clone())
+    ///     .or_insert_with(|| FormError("first"));
+    /// // the key is now occupied, so this closure never runs
+    /// errors.entry(key).or_insert_with(|| FormError("second"));
+    ///
+    /// let (_, error) = errors.iter().next().unwrap();
+    /// assert_eq!(error.to_string(), "first");
+    /// ```
+    #[inline(always)]
+    pub fn entry(&mut self, key: ErrorKey) -> Entry<'_> {
+        Entry { errors: self, key }
+    }
+
+    /// An iterator over all the errors: first those for the keys reserved
+    /// with [`Errors::reserve_keys`], in the order in which they were
+    /// reserved, then the others in the order in which they were inserted.
+    #[inline(always)]
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.entries.iter())
+    }
+
+    /// Iterates over the errors for which `f` returns `true`, in the order
+    /// of [`Errors::iter`], without cloning or allocating, e.g. so that a
+    /// section only renders the errors it owns.
+    pub fn only<'a, F>(
+        &'a self,
+        mut f: F,
+    ) -> impl Iterator<Item = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>)>
+    where
+        F: FnMut(&ErrorKey, &Arc<dyn Error + Send + Sync>) -> bool + 'a,
+    {
+        self.iter().filter(move |(key, error)| f(key, error))
+    }
+
+    /// Iterates over the errors whose keys are namespaced by `prefix`,
+    /// including nested prefixes, like [`Errors::only`].
+    ///
+    /// ```
+    /// # use leptos_dom::{ErrorKey, ErrorKeyPrefix, Errors};
+    /// # #[derive(Debug)]
+    /// # struct Invalid;
+    /// # impl std::fmt::Display for Invalid {
+    /// #     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// #         f.write_str("invalid")
+    /// #     }
+    /// # }
+    /// # impl std::error::Error for Invalid {}
+    /// let billing = ErrorKeyPrefix::new("billing");
+    /// let mut errors = Errors::default();
+    /// errors.insert(billing.key("card"), Invalid);
+    /// errors.insert(ErrorKey::from("shipping:zip"), Invalid);
+    /// errors.insert(billing.key("address"), Invalid);
+    ///
+    /// let keys = errors
+    ///     .only_prefix(&billing)
+    ///     .map(|(key, _)| key.as_str())
+    ///     .collect::<Vec<_>>();
+    /// assert_eq!(keys, ["billing:card", "billing:address"]);
+    /// ```
+    pub fn only_prefix<'a>(
+        &'a self,
+        prefix: &'a ErrorKeyPrefix,
+    ) -> impl Iterator<Item = (&'a ErrorKey, &'a Arc<dyn Error + Send + Sync>)>
+    {
+        self.only(move |key, _| prefix.contains(key))
+    }
+
+    /// Like [`Errors::iter`], but also yields the id of each error, which
+    /// stays the same for as long as the error is stored, and is never
+    /// reused. Pass it to [`Errors::dismiss`] to remove that error.
+    pub fn iter_with_ids(
+        &self,
+    ) -> impl Iterator<Item = (u64, &ErrorKey, &Arc<dyn Error + Send + Sync>)>
+    {
+        self.entries
+            .iter()
+            .map(|(key, entry)| (entry.id, key, &entry.error))
+    }
+
+    /// Like [`Errors::iter`], but also yields the number of
+    /// [`source`](Error::source)s in the chain of each error, e.g. `0` for an
+    /// error without a source, which lets a fallback indent errors or show
+    /// how much detail they have without walking the chain itself.
+    ///
+    /// The depth is capped at 32, in case an error's sources form a cycle.
+    pub fn iter_with_depth(
+        &self,
+    ) -> impl Iterator<Item = (&ErrorKey, &Arc<dyn Error + Send + Sync>, usize)>
+    {
+        const MAX_DEPTH: usize = 32;
+
+        self.entries.iter().map(|(key, entry)| {
+            let depth = ErrorChain::new(&*entry.error)
+                .skip(1)
+                .take(MAX_DEPTH)
+                .count();
+            (key, &entry.error, depth)
+        })
+    }
+
+    /// A parallel iterator over all the errors, in arbitrary order, for
+    /// processing large sets of errors on the server.
+    ///
+    /// This is only available with the `rayon` feature, and not on `wasm32`.
+    #[cfg(all(feature = "rayon", not(target_arch = "wasm32")))]
+    pub fn par_iter(
+        &self,
+    ) -> impl rayon::iter::ParallelIterator<
+        Item = (&ErrorKey, &Arc<dyn Error + Send + Sync>),
+    > {
+        use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+        self.entries
+            .par_iter()
+            .map(|(key, entry)| (key, &entry.error))
     }
 }
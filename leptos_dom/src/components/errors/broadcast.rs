@@ -0,0 +1,74 @@
+use super::{ErrorReport, Errors};
+use futures::{stream, Stream, StreamExt};
+use leptos_reactive::{
+    create_isomorphic_effect, signal_prelude::*, RwSignal, Scope,
+};
+use tokio::sync::watch;
+
+/// Broadcasts every change to `errors` to async consumers, e.g., a server
+/// task that aborts a response stream once the render it belongs to fails.
+///
+/// The receiver starts out with the current errors, and is marked as changed
+/// after every later update to the signal. This is meant for server-side code:
+/// in the browser, react to the signal directly instead.
+///
+/// ## Cancellation
+/// The broadcast lasts as long as `cx`. Once `cx` is disposed, the sender is
+/// dropped, so [`Receiver::changed`](watch::Receiver::changed) returns an
+/// error and any loop awaiting it ends. Dropping every receiver does not stop
+/// the broadcast, but the errors are no longer cloned on each change.
+pub fn watch_errors(
+    cx: Scope,
+    errors: RwSignal<Errors>,
+) -> watch::Receiver<Errors> {
+    let (tx, rx) = watch::channel(errors.get_untracked());
+    create_isomorphic_effect(cx, move |prev: Option<()>| {
+        errors.with(|errors| {
+            // the receiver already holds the errors of the first run
+            if prev.is_some() && !tx.is_closed() {
+                _ = tx.send(errors.clone());
+            }
+        });
+    });
+    rx
+}
+
+/// Streams an untagged [ErrorReport] for every error added to `errors`
+/// after this is called, e.g. to relay the errors of a render to a
+/// monitoring endpoint as server-sent events. The stream ends once `cx` is
+/// disposed.
+///
+/// ## Backpressure
+/// The stream is built on [`watch_errors`], which never blocks the reactive
+/// system: if the consumer falls behind, the changes since it last polled
+/// are coalesced, and it gets a report for every error added in the
+/// meantime that is still there. Errors that were added and removed again
+/// before the stream was polled are skipped, so a slow consumer drops
+/// short-lived errors rather than slowing down the render.
+pub fn error_report_stream(
+    cx: Scope,
+    errors: RwSignal<Errors>,
+) -> impl Stream<Item = ErrorReport> + Send + 'static {
+    let rx = watch_errors(cx, errors);
+    let version = rx.borrow().version;
+    stream::unfold((rx, version), |(mut rx, mut version)| async move {
+        rx.changed().await.ok()?;
+        let reports = {
+            let errors = rx.borrow_and_update();
+            // the errors were replaced by a new set with its own versions
+            if errors.version < version {
+                version = 0;
+            }
+            let reports = errors
+                .entries
+                .iter()
+                .filter(|(_, entry)| entry.version > version)
+                .map(|(key, entry)| entry.to_report(key, None))
+                .collect::<Vec<_>>();
+            version = errors.version;
+            reports
+        };
+        Some((stream::iter(reports), (rx, version)))
+    })
+    .flatten()
+}
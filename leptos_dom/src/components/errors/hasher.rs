@@ -0,0 +1,91 @@
+use rustc_hash::FxHasher;
+use std::{
+    collections::hash_map::{DefaultHasher, RandomState},
+    hash::{BuildHasher, Hasher},
+};
+
+/// The hasher that the map backing [Errors](super::Errors) uses for lookups,
+/// chosen with [`Errors::with_hasher`](super::Errors::with_hasher).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ErrorHasher {
+    /// The randomly seeded std hasher, which is resistant to HashDoS attacks.
+    #[default]
+    Std,
+    /// The faster `FxHasher`, which is not DoS-resistant: only use it if no
+    /// key can come from user input, e.g. from
+    /// [`Errors::extend_from_json`](super::Errors::extend_from_json) or an
+    /// [ErrorRegistry](super::ErrorRegistry).
+    Fx,
+}
+
+/// The [BuildHasher] of the map, with the seed of the std hasher.
+#[derive(Clone)]
+pub(super) enum MapHasher {
+    Std(RandomState),
+    Fx,
+}
+
+impl From<ErrorHasher> for MapHasher {
+    fn from(hasher: ErrorHasher) -> Self {
+        match hasher {
+            ErrorHasher::Std => Self::Std(RandomState::new()),
+            ErrorHasher::Fx => Self::Fx,
+        }
+    }
+}
+
+impl Default for MapHasher {
+    fn default() -> Self {
+        ErrorHasher::default().into()
+    }
+}
+
+impl BuildHasher for MapHasher {
+    type Hasher = MapHasherState;
+
+    fn build_hasher(&self) -> MapHasherState {
+        match self {
+            Self::Std(state) => MapHasherState::Std(state.build_hasher()),
+            Self::Fx => MapHasherState::Fx(FxHasher::default()),
+        }
+    }
+}
+
+pub(super) enum MapHasherState {
+    Std(DefaultHasher),
+    Fx(FxHasher),
+}
+
+impl Hasher for MapHasherState {
+    #[inline]
+    fn finish(&self) -> u64 {
+        match self {
+            Self::Std(hasher) => hasher.finish(),
+            Self::Fx(hasher) => hasher.finish(),
+        }
+    }
+
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Std(hasher) => hasher.write(bytes),
+            Self::Fx(hasher) => hasher.write(bytes),
+        }
+    }
+
+    #[inline]
+    fn write_u8(&mut self, i: u8) {
+        match self {
+            Self::Std(hasher) => hasher.write_u8(i),
+            Self::Fx(hasher) => hasher.write_u8(i),
+        }
+    }
+
+    #[inline]
+    fn write_usize(&mut self, i: usize) {
+        match self {
+            Self::Std(hasher) => hasher.write_usize(i),
+            Self::Fx(hasher) => hasher.write_usize(i),
+        }
+    }
+}
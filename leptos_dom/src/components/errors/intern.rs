@@ -0,0 +1,135 @@
+use once_cell::sync::Lazy;
+use std::{
+    borrow::Cow,
+    cmp::Ordering,
+    collections::HashSet,
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Deref,
+    sync::{Arc, Mutex},
+};
+
+/// Every key created with [`ErrorKey::interned`](super::ErrorKey::interned),
+/// so that equal keys share one allocation.
+static INTERNER: Lazy<Mutex<HashSet<Arc<str>>>> = Lazy::new(Default::default);
+
+/// The string of an [ErrorKey](super::ErrorKey) with the `intern-keys`
+/// feature: either a string literal, an owned string, or an interned string
+/// that is shared by all keys with the same contents.
+///
+/// It behaves exactly like the string it holds, so keys compare, hash and
+/// sort the same whether or not they were interned.
+#[derive(Clone)]
+pub(super) enum KeyStr {
+    Static(&'static str),
+    Owned(String),
+    Interned(Arc<str>),
+}
+
+impl KeyStr {
+    pub(super) fn intern(key: &str) -> Self {
+        let mut interner = INTERNER.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(interned) = interner.get(key) {
+            return Self::Interned(Arc::clone(interned));
+        }
+        let interned: Arc<str> = key.into();
+        interner.insert(Arc::clone(&interned));
+        Self::Interned(interned)
+    }
+}
+
+impl Default for KeyStr {
+    fn default() -> Self {
+        Self::Static("")
+    }
+}
+
+impl Deref for KeyStr {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            Self::Static(key) => key,
+            Self::Owned(key) => key,
+            Self::Interned(key) => key,
+        }
+    }
+}
+
+impl From<&'static str> for KeyStr {
+    fn from(key: &'static str) -> Self {
+        Self::Static(key)
+    }
+}
+
+impl From<&'static String> for KeyStr {
+    fn from(key: &'static String) -> Self {
+        Self::Static(key)
+    }
+}
+
+impl From<String> for KeyStr {
+    fn from(key: String) -> Self {
+        Self::Owned(key)
+    }
+}
+
+impl From<Cow<'static, str>> for KeyStr {
+    fn from(key: Cow<'static, str>) -> Self {
+        match key {
+            Cow::Borrowed(key) => Self::Static(key),
+            Cow::Owned(key) => Self::Owned(key),
+        }
+    }
+}
+
+impl PartialEq for KeyStr {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for KeyStr {}
+
+impl PartialOrd for KeyStr {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for KeyStr {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (**self).cmp(&**other)
+    }
+}
+
+impl Hash for KeyStr {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (**self).hash(state)
+    }
+}
+
+impl fmt::Debug for KeyStr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for KeyStr {
+    fn serialize<S: serde::Serializer>(
+        &self,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for KeyStr {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        String::deserialize(deserializer).map(Self::from)
+    }
+}
@@ -0,0 +1,403 @@
+use super::{Error, ErrorChain, ErrorEntry, ErrorKey, Errors};
+use crate::{html, IntoView, View};
+use leptos_reactive::{
+    create_resource, provide_context, signal_prelude::*, use_context, Resource,
+    RwSignal, Scope,
+};
+use std::{
+    borrow::Cow,
+    future::{self, Future},
+    pin::Pin,
+    rc::Rc,
+    sync::Arc,
+};
+
+/// Options for the built-in rendering of [Errors] by [`errors_view`].
+#[derive(Debug, Clone)]
+pub struct ErrorViewOptions {
+    /// Whether the `<details>` disclosure listing the sources of each error
+    /// is expanded by default.
+    pub open: bool,
+    /// The attribute holding the [ErrorKey](super::ErrorKey) of each rendered
+    /// error, which gives end-to-end tests a stable selector. Defaults to
+    /// `data-error-key`.
+    pub key_attribute: Cow<'static, str>,
+    /// The attribute holding the [ErrorSeverity](super::ErrorSeverity) of
+    /// each rendered error. Defaults to `data-error-severity`.
+    pub severity_attribute: Cow<'static, str>,
+    /// Whether errors inserted with [`Errors::insert_html`] are rendered
+    /// from their [HtmlError] markup rather than as text. Defaults to
+    /// `false`.
+    ///
+    /// The markup is injected as is, so only enable this if every such
+    /// error type only returns trusted, sanitized HTML: otherwise, this is a
+    /// cross-site scripting vulnerability.
+    pub trusted_html: bool,
+}
+
+impl Default for ErrorViewOptions {
+    fn default() -> Self {
+        Self {
+            open: false,
+            key_attribute: "data-error-key".into(),
+            severity_attribute: "data-error-severity".into(),
+            trusted_html: false,
+        }
+    }
+}
+
+/// Renders all the errors as a list. Each item shows the error's message;
+/// errors with a [`source`](Error::source) also get a `<details>` disclosure
+/// that can be expanded to show the full chain of sources.
+///
+/// Errors with a [UserFacing](super::UserFacing) message only show that
+/// message, as their source chain is meant for developers. With
+/// [`ErrorViewOptions::trusted_html`], errors inserted with
+/// [`Errors::insert_html`] show their HTML instead. The message of an error
+/// inserted with [`Errors::insert_cached`] is taken from the cache rather
+/// than formatted again.
+pub fn errors_view(
+    cx: Scope,
+    errors: &Errors,
+    options: &ErrorViewOptions,
+) -> View {
+    error_list(cx, errors.entries.iter(), options)
+}
+
+fn error_list<'a>(
+    cx: Scope,
+    entries: impl Iterator<Item = (&'a ErrorKey, &'a ErrorEntry)>,
+    options: &ErrorViewOptions,
+) -> View {
+    let items = entries
+        .map(|(key, entry)| {
+            let markup = entry
+                .html
+                .filter(|_| options.trusted_html)
+                .and_then(|html| html(entry.hooked_error()));
+            let content = match markup {
+                Some(markup) => html::div(cx).inner_html(markup).into_view(cx),
+                None => text_view(cx, entry, options.open),
+            };
+            html::li(cx)
+                .attr(options.key_attribute.clone(), key.as_str().to_string())
+                .attr(
+                    options.severity_attribute.clone(),
+                    entry.severity.as_str(),
+                )
+                .child(content)
+        })
+        .collect::<Vec<_>>();
+
+    html::ul(cx).attr("role", "list").child(items).into_view(cx)
+}
+
+/// Renders the errors like [`errors_view`], but grouped into a `<section>`
+/// for each [namespace](ErrorKey::namespace), in the order in which the
+/// namespaces first appear. Each section starts with an `<h3>` heading,
+/// whose text `heading` derives from the namespace, e.g. to turn `billing`
+/// into `Billing address`.
+///
+/// Errors without a namespace are listed after the sections, without a
+/// heading.
+pub fn grouped_errors_view(
+    cx: Scope,
+    errors: &Errors,
+    options: &ErrorViewOptions,
+    heading: impl Fn(&str) -> String,
+) -> View {
+    let mut groups: Vec<(&str, Vec<(&ErrorKey, &ErrorEntry)>)> = Vec::new();
+    let mut ungrouped = Vec::new();
+    for (key, entry) in &errors.entries {
+        let namespace = match key.namespace() {
+            Some(namespace) => namespace,
+            None => {
+                ungrouped.push((key, entry));
+                continue;
+            }
+        };
+        match groups.iter_mut().find(|(name, _)| *name == namespace) {
+            Some((_, group)) => group.push((key, entry)),
+            None => groups.push((namespace, vec![(key, entry)])),
+        }
+    }
+
+    let mut sections = groups
+        .into_iter()
+        .map(|(namespace, group)| {
+            html::section(cx)
+                .child(html::h3(cx).child(heading(namespace)))
+                .child(error_list(cx, group.into_iter(), options))
+                .into_view(cx)
+        })
+        .collect::<Vec<_>>();
+    if !ungrouped.is_empty() {
+        sections.push(error_list(cx, ungrouped.into_iter(), options));
+    }
+    sections.into_view(cx)
+}
+
+/// An error that carries its own, pre-rendered HTML, e.g. an error page
+/// fragment provided by the server.
+///
+/// Errors inserted with [`Errors::insert_html`] are rendered from this HTML
+/// by [`errors_view`], but only if [`ErrorViewOptions::trusted_html`] is
+/// enabled. Otherwise, they are rendered as text like other errors.
+///
+/// The HTML is not escaped, so it must be trusted and sanitized: returning
+/// any user-provided content from it is a cross-site scripting
+/// vulnerability.
+pub trait HtmlError {
+    /// The HTML describing the error.
+    fn html(&self) -> String;
+}
+
+/// Renders the [UserFacing](super::UserFacing) message of an entry if it
+/// has one, and its [`error_details_view`] otherwise. Both are rendered as a
+/// `<pre>` block instead if the error is [multiline](DisplayHint::multiline).
+///
+/// The message of an error inserted with [`Errors::insert_cached`] is taken
+/// from the cache rather than formatted again.
+fn text_view(cx: Scope, entry: &ErrorEntry, open: bool) -> View {
+    match (entry.is_multiline(), entry.user_facing_message()) {
+        (true, _) => html::pre(cx).child(entry.message()).into_view(cx),
+        (false, Some(message)) if !message.is_empty() => message.into_view(cx),
+        (false, _) => {
+            details_view(cx, entry.display_message(), &*entry.error, open)
+        }
+    }
+}
+
+/// A hint about how an error's message should be laid out when it is
+/// rendered by [`errors_view`] or [`Errors::render_each`] without a
+/// formatter.
+///
+/// Errors inserted with [`Errors::insert_with_hint`] whose message spans
+/// several lines, e.g. a compiler diagnostic or a stack of validation
+/// messages, are rendered as a `<pre>` block, which keeps their line breaks.
+/// All other errors are rendered inline.
+pub trait DisplayHint {
+    /// Whether the message of the error spans several lines.
+    fn multiline(&self) -> bool;
+}
+
+fn downcast_multiline<E>(error: &(dyn Error + 'static)) -> bool
+where
+    E: Error + DisplayHint + 'static,
+{
+    error
+        .downcast_ref::<E>()
+        .map(DisplayHint::multiline)
+        .unwrap_or(false)
+}
+
+impl ErrorEntry {
+    fn is_multiline(&self) -> bool {
+        self.multiline
+            .map(|multiline| multiline(self.hooked_error()))
+            .unwrap_or(false)
+    }
+}
+
+fn downcast_html<E>(error: &(dyn Error + 'static)) -> Option<String>
+where
+    E: Error + HtmlError + 'static,
+{
+    error.downcast_ref::<E>().map(HtmlError::html)
+}
+
+type Formatter = Rc<dyn Fn(Scope, &(dyn Error + 'static)) -> Option<View>>;
+
+/// The chain of formatters provided by [`provide_error_formatter`], innermost
+/// first.
+#[derive(Clone, Default)]
+struct ErrorFormatters(Vec<Formatter>);
+
+/// Provides a formatter that [`Errors::render_each`] uses to render errors
+/// in this scope and its children, e.g. to localize messages.
+///
+/// The formatter returns `None` for errors it doesn't handle, which are then
+/// passed to the formatters provided in parent scopes, and finally rendered
+/// like [`errors_view`] does if no formatter handles them.
+pub fn provide_error_formatter(
+    cx: Scope,
+    formatter: impl Fn(Scope, &(dyn Error + 'static)) -> Option<View> + 'static,
+) {
+    let mut formatters = use_context::<ErrorFormatters>(cx).unwrap_or_default();
+    formatters.0.insert(0, Rc::new(formatter));
+    provide_context(cx, formatters);
+}
+
+type AsyncFormatter = Rc<
+    dyn Fn(
+        Arc<dyn Error + Send + Sync>,
+    ) -> Pin<Box<dyn Future<Output = String>>>,
+>;
+
+/// The formatter provided by [`provide_async_error_formatter`].
+#[derive(Clone)]
+struct AsyncErrorFormatter(AsyncFormatter);
+
+/// Provides a formatter that turns errors into human-readable messages
+/// asynchronously, e.g. by looking up an error code on the server, for use
+/// with [`use_async_error_formatter`] in this scope and its children.
+///
+/// A formatter provided in a child scope replaces the one of its parent.
+pub fn provide_async_error_formatter<F, Fu>(cx: Scope, formatter: F)
+where
+    F: Fn(Arc<dyn Error + Send + Sync>) -> Fu + 'static,
+    Fu: Future<Output = String> + 'static,
+{
+    let formatter: AsyncFormatter =
+        Rc::new(move |error| Box::pin(formatter(error)));
+    provide_context(cx, AsyncErrorFormatter(formatter));
+}
+
+/// Returns a function that creates a [Resource] with the message of an error,
+/// as resolved by the formatter of [`provide_async_error_formatter`]. Without
+/// one, the resource resolves to the error's [`Display`](std::fmt::Display)
+/// output right away.
+///
+/// The resources are created with
+/// [`create_resource`](leptos_reactive::create_resource) in `cx`, so they take
+/// part in the suspense system: read them inside a `<Suspense/>` in the
+/// `fallback` of an `<ErrorBoundary/>` to show a placeholder while the message
+/// is loading. Like any other resource, a message resolved while rendering on
+/// the server is serialized and sent along with the page, so the client
+/// doesn't look it up again while hydrating.
+pub fn use_async_error_formatter(
+    cx: Scope,
+) -> impl Fn(Arc<dyn Error + Send + Sync>) -> Resource<(), String> {
+    let formatter = use_context::<AsyncErrorFormatter>(cx);
+    move |error| {
+        let formatter = formatter.clone();
+        create_resource(
+            cx,
+            || (),
+            move |_| -> Pin<Box<dyn Future<Output = String>>> {
+                match &formatter {
+                    Some(formatter) => (formatter.0)(Arc::clone(&error)),
+                    None => Box::pin(future::ready(error.to_string())),
+                }
+            },
+        )
+    }
+}
+
+impl Errors {
+    /// Add an error that carries its own markup, which [`errors_view`]
+    /// renders when [`ErrorViewOptions::trusted_html`] is enabled. See
+    /// [HtmlError].
+    pub fn insert_html<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + HtmlError + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.html = Some(downcast_html::<E>);
+        self.insert_entry(key, entry);
+    }
+
+    /// Add an error whose layout is chosen by its [DisplayHint] when it is
+    /// rendered.
+    pub fn insert_with_hint<E>(&mut self, key: ErrorKey, error: E)
+    where
+        E: Error + DisplayHint + Send + Sync + 'static,
+    {
+        let mut entry = ErrorEntry::typed(error);
+        entry.multiline = Some(downcast_multiline::<E>);
+        self.insert_entry(key, entry);
+    }
+
+    /// Returns `true` if the error with the given key was inserted with
+    /// [`Errors::insert_with_hint`] and its [DisplayHint] says it spans
+    /// several lines. Other errors are single-line.
+    pub fn is_multiline(&self, key: &ErrorKey) -> bool {
+        self.entries
+            .get(key)
+            .map(ErrorEntry::is_multiline)
+            .unwrap_or(false)
+    }
+
+    /// Renders each error with the formatters provided by
+    /// [`provide_error_formatter`], in insertion order, like
+    /// [`Errors::iter`]. This lets custom fallbacks lay out errors however
+    /// they like, while rendering each one consistently.
+    pub fn render_each<'a>(
+        &'a self,
+        cx: Scope,
+    ) -> impl Iterator<Item = (ErrorKey, View)> + 'a {
+        let formatters = use_context::<ErrorFormatters>(cx).unwrap_or_default();
+        self.entries.iter().map(move |(key, entry)| {
+            let formatted = formatters
+                .0
+                .iter()
+                .find_map(|formatter| formatter(cx, &*entry.error));
+            let view = match formatted {
+                Some(view) => view,
+                None => text_view(cx, entry, false),
+            };
+            (key.clone(), view)
+        })
+    }
+}
+
+/// A default fallback for `<ErrorBoundary/>`, which can be passed to it
+/// directly as `fallback=default_error_fallback`.
+///
+/// Renders an `alert` region containing the [`errors_view`] of the current
+/// errors with the default [ErrorViewOptions], and updates as errors are
+/// added or removed.
+pub fn default_error_fallback(cx: Scope, errors: RwSignal<Errors>) -> View {
+    let options = ErrorViewOptions::default();
+    html::div(cx)
+        .attr("role", "alert")
+        .child(move || errors.with(|errors| errors_view(cx, errors, &options)))
+        .into_view(cx)
+}
+
+/// Renders a single error as a `<details>` disclosure, with the error's
+/// message as the `<summary>` and its chain of sources as the content.
+///
+/// If the error has no source, only its message is rendered. Errors whose
+/// [`Display`](std::fmt::Display) output is empty are shown with their
+/// [`Debug`](std::fmt::Debug) output instead, so that the message is never
+/// blank.
+pub fn error_details_view(
+    cx: Scope,
+    error: &(dyn Error + 'static),
+    open: bool,
+) -> View {
+    details_view(cx, message(error), error, open)
+}
+
+/// Renders `summary` as the message of `error`, like [`error_details_view`].
+fn details_view(
+    cx: Scope,
+    summary: String,
+    error: &(dyn Error + 'static),
+    open: bool,
+) -> View {
+    let sources = ErrorChain::new(error)
+        .skip(1)
+        .map(|source| html::li(cx).child(message(source)))
+        .collect::<Vec<_>>();
+
+    if sources.is_empty() {
+        return summary.into_view(cx);
+    }
+
+    html::details(cx)
+        .attr("open", open)
+        .child(html::summary(cx).child(summary))
+        .child(html::ol(cx).child(sources))
+        .into_view(cx)
+}
+
+fn message(error: &(dyn Error + 'static)) -> String {
+    let message = error.to_string();
+    if message.is_empty() {
+        format!("{error:?}")
+    } else {
+        message
+    }
+}
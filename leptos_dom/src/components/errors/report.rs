@@ -0,0 +1,232 @@
+use super::{use_errors, Error, ErrorEntry, ErrorKey, Errors};
+use leptos_reactive::{create_memo, signal_prelude::*, Memo, Scope};
+use std::{any::TypeId, borrow::Cow, collections::HashMap, fmt, sync::Arc};
+
+/// A plain description of an error in [Errors], e.g. to transfer errors
+/// collected during server rendering to the client.
+///
+/// With the `serde` feature, this can be serialized and deserialized.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ErrorReport {
+    /// The key of the error.
+    pub key: ErrorKey,
+    /// The [`Display`](fmt::Display) output of the error.
+    pub message: String,
+    /// The tag the error's type was registered with in an [ErrorRegistry],
+    /// if any.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub tag: Option<Cow<'static, str>>,
+    /// The formatted `SpanTrace` that was captured when the error was
+    /// inserted, which is only recorded with the `tracing-error` feature.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    pub span_trace: Option<String>,
+}
+
+/// A generic error reconstructed from an [ErrorReport], which only preserves
+/// the message of the original error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServerError {
+    message: String,
+}
+
+impl ServerError {
+    /// Creates an error with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+        }
+    }
+
+    /// The message of the original error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for ServerError {}
+
+impl ErrorEntry {
+    pub(super) fn to_report(
+        &self,
+        key: &ErrorKey,
+        tag: Option<Cow<'static, str>>,
+    ) -> ErrorReport {
+        ErrorReport {
+            key: key.clone(),
+            message: self.display_message(),
+            tag,
+            span_trace: self.span_trace_string(),
+        }
+    }
+}
+
+type FromMessage = fn(String) -> Arc<dyn Error + Send + Sync>;
+
+/// Maps error types to tags, so that errors transferred as [ErrorReport]s
+/// can be reconstructed as their original type rather than a [ServerError].
+///
+/// Both sides register the same types under the same tags: the server uses
+/// the registry to tag the reports it sends with [`ErrorRegistry::to_reports`],
+/// and the client uses it to turn the reports it receives back into errors
+/// with [`ErrorRegistry::to_errors`].
+///
+/// ```
+/// # use leptos_dom::{ErrorKey, ErrorRegistry, Errors};
+/// # use std::{error::Error, fmt, sync::Arc};
+/// #[derive(Debug)]
+/// struct NotFound(String);
+///
+/// impl fmt::Display for NotFound {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         f.write_str(&self.0)
+///     }
+/// }
+///
+/// impl Error for NotFound {}
+///
+/// let mut registry = ErrorRegistry::new();
+/// registry.register::<NotFound>("not-found", |message| {
+///     Arc::new(NotFound(message))
+/// });
+///
+/// let mut errors = Errors::default();
+/// errors.insert(ErrorKey::from("user"), NotFound("no such user".into()));
+///
+/// let reports = registry.to_reports(&errors);
+/// assert_eq!(reports[0].tag.as_deref(), Some("not-found"));
+///
+/// let errors = registry.to_errors(reports);
+/// let (_, error) = errors.iter().next().unwrap();
+/// assert!(error.is::<NotFound>());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ErrorRegistry {
+    tags: HashMap<TypeId, Cow<'static, str>>,
+    constructors: HashMap<Cow<'static, str>, FromMessage>,
+}
+
+impl ErrorRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the error type `E` under `tag`, with a function that
+    /// reconstructs it from its message.
+    ///
+    /// Only errors whose concrete type was known when inserted into [Errors]
+    /// can be tagged. Errors of unregistered types, or reports with an
+    /// unknown tag, fall back to a [ServerError].
+    pub fn register<E>(
+        &mut self,
+        tag: impl Into<Cow<'static, str>>,
+        from_message: FromMessage,
+    ) -> &mut Self
+    where
+        E: Error + 'static,
+    {
+        let tag = tag.into();
+        self.tags.insert(TypeId::of::<E>(), tag.clone());
+        self.constructors.insert(tag, from_message);
+        self
+    }
+
+    fn tag_of(&self, entry: &ErrorEntry) -> Option<Cow<'static, str>> {
+        // the type of an error replaced through an `Entry` is unknown
+        let type_id = entry.typed_any()?.as_ref().type_id();
+        self.tags.get(&type_id).cloned()
+    }
+
+    /// Describes every error as an [ErrorReport], tagged with the tag of its
+    /// type if it has been registered.
+    pub fn to_reports(&self, errors: &Errors) -> Vec<ErrorReport> {
+        errors
+            .entries
+            .iter()
+            .map(|(key, entry)| entry.to_report(key, self.tag_of(entry)))
+            .collect()
+    }
+
+    /// Reconstructs errors from their reports, using the type registered for
+    /// each tag, or a [ServerError] if there is none.
+    pub fn to_errors(
+        &self,
+        reports: impl IntoIterator<Item = ErrorReport>,
+    ) -> Errors {
+        let mut errors = Errors::default();
+        for report in reports {
+            let constructor = report
+                .tag
+                .as_ref()
+                .and_then(|tag| self.constructors.get(tag));
+            let error = match constructor {
+                Some(from_message) => from_message(report.message),
+                None => Arc::new(ServerError::new(report.message)),
+            };
+            errors.insert_entry(report.key, ErrorEntry::new(error));
+        }
+        errors
+    }
+}
+
+impl Errors {
+    /// Describes every error as an untagged [ErrorReport]. See
+    /// [`ErrorRegistry::to_reports`] to tag them with their types.
+    pub fn to_reports(&self) -> Vec<ErrorReport> {
+        ErrorRegistry::default().to_reports(self)
+    }
+
+    /// Inserts the errors described by a JSON array, e.g. the validation
+    /// errors returned by a server for a form submission, as
+    /// [ServerError]s. Each item has the shape of a serialized
+    /// [ErrorReport]:
+    ///
+    /// ```json
+    /// [
+    ///   { "key": "email", "message": "already registered" },
+    ///   { "key": "password", "message": "too short" }
+    /// ]
+    /// ```
+    ///
+    /// Any other fields of the items are ignored. If `value` doesn't have
+    /// this shape, returns an error and inserts nothing.
+    #[cfg(feature = "serde")]
+    pub fn extend_from_json(
+        &mut self,
+        value: &serde_json::Value,
+    ) -> Result<(), serde_json::Error> {
+        use serde::Deserialize;
+
+        for report in Vec::<ErrorReport>::deserialize(value)? {
+            self.insert(report.key, ServerError::new(report.message));
+        }
+        Ok(())
+    }
+}
+
+/// Returns the errors of the nearest `<ErrorBoundary/>` as untagged
+/// [ErrorReport]s, e.g. to `.map()` over them in a custom fallback.
+///
+/// The reports are memoized, so anything that reads them only reruns when
+/// they actually change, rather than every time the errors signal is
+/// updated. If there is no boundary, the list is always empty.
+pub fn use_error_reports(cx: Scope) -> Memo<Vec<ErrorReport>> {
+    let errors = use_errors(cx);
+    create_memo(cx, move |_| match errors {
+        Some(errors) => errors.with(Errors::to_reports),
+        None => Vec::new(),
+    })
+}
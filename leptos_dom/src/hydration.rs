@@ -101,6 +101,12 @@ impl HydrationCtx {
         ID.with(|id| id.borrow().clone())
     }
 
+    /// Calls `f` with the key of the previous component, without cloning the
+    /// current hydration `id`.
+    pub(crate) fn with_previous<T>(f: impl FnOnce(&str) -> T) -> T {
+        ID.with(|id| f(&id.borrow().previous))
+    }
+
     /// Increments the current hydration `id` and returns it
     pub fn id() -> HydrationKey {
         ID.with(|id| {
@@ -0,0 +1,2446 @@
+use leptos_dom::{
+    assert_no_errors, cleanup_error, clear_errors_on, clear_scope,
+    default_error_fallback, errors_view, grouped_errors_view,
+    has_error_boundary, has_error_handler, modify_errors,
+    provide_error_enricher, provide_error_filter, provide_error_formatter,
+    provide_error_key_anchor, provide_error_key_prefix, provide_error_metrics,
+    provide_error_sink, provide_key_strategy, render_results, report_error,
+    report_error_async, retryable_view, suppress_errors_in_subtree,
+    use_error_reports, use_errors, ContextError, DisplayHint, ErrorChain,
+    ErrorHasher, ErrorKey, ErrorKeyEnum, ErrorKeyPrefix, ErrorKind,
+    ErrorSeverity, ErrorSink, ErrorViewOptions, Errors, HtmlError,
+    HydrationCtx, HydrationKey, IntoView, ResultViewExt, Retryable, UserFacing,
+};
+use leptos_reactive::{
+    create_isomorphic_effect, create_runtime, create_rw_signal, create_scope,
+    provide_context, signal_prelude::*, ScopeDisposer,
+};
+use std::{
+    collections::HashMap,
+    error::Error,
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Debug)]
+struct TestError {
+    message: &'static str,
+    source: Option<Box<TestError>>,
+}
+
+impl TestError {
+    fn new(message: &'static str) -> Self {
+        Self {
+            message,
+            source: None,
+        }
+    }
+
+    fn caused_by(mut self, source: TestError) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+}
+
+impl fmt::Display for TestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.message)
+    }
+}
+
+impl Error for TestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.source.as_deref().map(|source| source as _)
+    }
+}
+
+#[test]
+fn errors_view_renders_details() {
+    _ = create_scope(create_runtime(), |cx| {
+        let mut errors = Errors::default();
+        errors.insert(
+            ErrorKey::from("load"),
+            TestError::new("failed to load")
+                .caused_by(TestError::new("connection refused")),
+        );
+
+        let closed = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(closed.contains("<details"));
+        assert!(!closed.contains("open"));
+        assert!(closed.contains("failed to load</summary>"));
+        assert!(closed.contains("connection refused</li>"));
+
+        let open = errors_view(
+            cx,
+            &errors,
+            &ErrorViewOptions {
+                open: true,
+                ..Default::default()
+            },
+        )
+        .render_to_string(cx);
+        assert!(open.contains("<details"));
+        assert!(open.contains(" open"));
+    });
+}
+
+#[derive(Debug)]
+struct QueryError;
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("SELECT * FROM users failed")
+    }
+}
+
+impl Error for QueryError {}
+
+impl UserFacing for QueryError {
+    fn user_message(&self) -> String {
+        "Could not load users.".to_string()
+    }
+}
+
+#[test]
+fn user_message_prefers_user_facing() {
+    let mut errors = Errors::default();
+    errors.insert_user_facing(ErrorKey::from("users"), QueryError);
+    errors.insert(ErrorKey::from("plain"), TestError::new("plain error"));
+
+    assert_eq!(
+        errors.user_message(&ErrorKey::from("users")).as_deref(),
+        Some("Could not load users.")
+    );
+    assert_eq!(
+        errors.user_message(&ErrorKey::from("plain")).as_deref(),
+        Some("plain error")
+    );
+    assert_eq!(errors.user_message(&ErrorKey::from("missing")), None);
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(html.contains("Could not load users."));
+        assert!(!html.contains("SELECT"));
+    });
+}
+
+#[test]
+fn remove_older_than_removes_stale_errors() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("old"), TestError::new("old"));
+    let inserted_at = errors.inserted_at(&ErrorKey::from("old")).unwrap();
+
+    let max_age = Duration::from_secs(60);
+    assert_eq!(errors.remove_older_than(max_age, inserted_at), 0);
+    assert_eq!(errors.remove_older_than(max_age, inserted_at + max_age), 0);
+    assert!(!errors.is_empty());
+
+    let later = inserted_at + max_age + Duration::from_millis(1);
+    assert_eq!(errors.remove_older_than(max_age, later), 1);
+    assert!(errors.is_empty());
+}
+
+#[test]
+fn clone_shallow_shares_errors() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), TestError::new("a"));
+    errors.insert(ErrorKey::from("b"), TestError::new("b"));
+
+    let cloned = errors.clone_shallow();
+    for (key, error) in errors.iter() {
+        let (_, cloned_error) = cloned
+            .iter()
+            .find(|(cloned_key, _)| *cloned_key == key)
+            .unwrap();
+        assert!(Arc::ptr_eq(error, cloned_error));
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct OtherError;
+
+impl fmt::Display for OtherError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("other error")
+    }
+}
+
+impl Error for OtherError {}
+
+#[test]
+fn into_single_returns_owned_error() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), OtherError);
+    assert_eq!(errors.into_single::<OtherError>().unwrap(), OtherError);
+}
+
+#[test]
+fn into_single_fails_when_empty() {
+    assert!(Errors::default().into_single::<OtherError>().is_err());
+}
+
+#[test]
+fn into_single_fails_with_multiple_errors() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), OtherError);
+    errors.insert(ErrorKey::from("b"), OtherError);
+    let errors = errors.into_single::<OtherError>().unwrap_err();
+    assert_eq!(errors.iter().count(), 2);
+}
+
+#[test]
+fn into_single_fails_with_wrong_type() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), TestError::new("a"));
+    let errors = errors.into_single::<OtherError>().unwrap_err();
+    assert_eq!(
+        errors.user_message(&ErrorKey::from("a")).as_deref(),
+        Some("a")
+    );
+}
+
+#[test]
+fn into_single_fails_when_shared() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), OtherError);
+    let shared = errors.clone_shallow();
+
+    let errors = errors.into_single::<OtherError>().unwrap_err();
+    assert!(!errors.is_empty());
+
+    // once the other handle is gone, it can be taken
+    drop(shared);
+    assert_eq!(errors.into_single::<OtherError>().unwrap(), OtherError);
+}
+
+#[test]
+fn errors_view_renders_data_attributes() {
+    let mut errors = Errors::default();
+    errors.insert_with_severity(
+        ErrorKey::from("email"),
+        TestError::new("invalid email"),
+        ErrorSeverity::Warning,
+    );
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(html.contains(r#"data-error-key="email""#));
+        assert!(html.contains(r#"data-error-severity="warning""#));
+
+        let options = ErrorViewOptions {
+            key_attribute: "data-testid".into(),
+            ..Default::default()
+        };
+        let html = errors_view(cx, &errors, &options).render_to_string(cx);
+        assert!(html.contains(r#"data-testid="email""#));
+        assert!(!html.contains("data-error-key"));
+    });
+}
+
+#[test]
+fn clear_scope_only_clears_own_errors() {
+    _ = create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let (widget_a, _) = cx.run_child_scope(|cx| {
+            provide_error_key_prefix(cx, "a");
+            Err::<(), _>(TestError::new("a failed")).into_view(cx);
+            cx
+        });
+        let (widget_b, _) = cx.run_child_scope(|cx| {
+            provide_error_key_prefix(cx, "b");
+            Err::<(), _>(TestError::new("b failed")).into_view(cx);
+            cx
+        });
+        let count = |prefix: &'static str| {
+            errors.with(|errors| {
+                errors
+                    .iter_with_prefix(&ErrorKeyPrefix::new(prefix))
+                    .count()
+            })
+        };
+        assert_eq!(count("a"), 1);
+        assert_eq!(count("b"), 1);
+
+        clear_scope(widget_a, errors);
+        assert_eq!(count("a"), 0);
+        assert_eq!(count("b"), 1);
+
+        clear_scope(widget_b, errors);
+        assert!(errors.with(Errors::is_empty));
+    });
+}
+
+#[test]
+fn nested_prefixes_are_contained_in_outer_prefix() {
+    _ = create_scope(create_runtime(), |cx| {
+        provide_error_key_prefix(cx, "form");
+        cx.child_scope(|cx| {
+            provide_error_key_prefix(cx, "address");
+            let prefix =
+                leptos_reactive::use_context::<ErrorKeyPrefix>(cx).unwrap();
+            assert_eq!(prefix.as_str(), "form/address");
+
+            let key = prefix.key("0-1");
+            assert!(ErrorKeyPrefix::new("form").contains(&key));
+            assert!(!ErrorKeyPrefix::new("for").contains(&key));
+        });
+    });
+}
+
+#[derive(Debug)]
+struct RedactedError;
+
+impl fmt::Display for RedactedError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("[redacted]")
+    }
+}
+
+impl Error for RedactedError {}
+
+#[test]
+fn map_errors_preserves_keys() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), TestError::new("user@example.com"));
+    errors.insert(ErrorKey::from("b"), TestError::new("555-0100"));
+
+    let mut keys = errors
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+    keys.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+    let redacted = errors.map_errors(|_| Arc::new(RedactedError));
+    let mut redacted_keys = redacted
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect::<Vec<_>>();
+    redacted_keys.sort_by(|a, b| a.as_str().cmp(b.as_str()));
+
+    assert_eq!(keys, redacted_keys);
+    assert!(redacted
+        .iter()
+        .all(|(_, error)| error.to_string() == "[redacted]"));
+}
+
+#[test]
+fn static_keys_equal_owned_keys() {
+    const KEY: ErrorKey = ErrorKey::from_static("email");
+    assert_eq!(KEY, ErrorKey::from("email".to_string()));
+}
+
+#[test]
+fn result_closure_reroutes_errors_on_each_run() {
+    _ = create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let succeed = create_rw_signal(cx, false);
+        let view = move || {
+            if succeed.get() {
+                Ok("loaded")
+            } else {
+                Err(TestError::new("failed"))
+            }
+        };
+        // re-runs the closure in a new scope whenever it changes, like the
+        // `DynChild` that renders it does in the browser
+        create_isomorphic_effect(cx, move |prev: Option<ScopeDisposer>| {
+            let (_, disposer) = cx.run_child_scope(|cx| view().into_view(cx));
+            if let Some(prev) = prev {
+                prev.dispose();
+            }
+            disposer
+        });
+        assert_eq!(errors.with(|errors| errors.iter().count()), 1);
+
+        succeed.set(true);
+        assert!(errors.with(Errors::is_empty));
+
+        succeed.set(false);
+        assert_eq!(errors.with(|errors| errors.iter().count()), 1);
+    });
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FieldError(&'static str);
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid field: {}", self.0)
+    }
+}
+
+impl Error for FieldError {}
+
+#[test]
+fn collect_type_returns_homogeneous_errors() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), FieldError("email"));
+    errors.insert(ErrorKey::from("b"), FieldError("name"));
+
+    let mut fields = errors.collect_type::<FieldError>();
+    fields.sort_by_key(|field| field.0);
+    assert_eq!(fields, vec![FieldError("email"), FieldError("name")]);
+}
+
+#[test]
+fn collect_type_skips_other_types() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), FieldError("email"));
+    errors.insert(ErrorKey::from("b"), OtherError);
+
+    assert_eq!(
+        errors.collect_type::<FieldError>(),
+        vec![FieldError("email")]
+    );
+    assert_eq!(errors.collect_type::<OtherError>(), vec![OtherError]);
+}
+
+#[test]
+fn report_error_inserts_under_the_given_key() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let key = ErrorKey::from("manual");
+        report_error(cx, key.clone(), FieldError("email"));
+
+        errors.with(|errors| {
+            let (found, error) = errors.iter().next().unwrap();
+            assert_eq!(found, &key);
+            assert_eq!(error.to_string(), "invalid field: email");
+        });
+    })
+    .dispose();
+}
+
+#[test]
+fn report_error_without_boundary_is_dropped() {
+    create_scope(create_runtime(), |cx| {
+        assert!(!has_error_boundary(cx));
+        report_error(cx, ErrorKey::from("manual"), FieldError("email"));
+    })
+    .dispose();
+}
+
+#[test]
+fn errors_behave_the_same_with_either_hasher() {
+    let build = |hasher| {
+        let mut errors = Errors::with_hasher(hasher);
+        for field in ["name", "email", "age"] {
+            errors.insert(ErrorKey::from(field.to_string()), FieldError(field));
+        }
+        errors.remove(&ErrorKey::from("email"));
+        errors
+    };
+    let keys = |errors: &Errors| {
+        errors
+            .iter()
+            .map(|(key, _)| key.as_str().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let std = build(ErrorHasher::Std);
+    let fx = build(ErrorHasher::Fx);
+    assert_eq!(keys(&fx), keys(&std));
+
+    let fx = fx.map_errors(|error| error);
+    let (age, rest) = fx.split(|key, _| key.as_str() == "age");
+    assert!(age.severity(&ErrorKey::from("age")).is_some());
+    assert!(rest.severity(&ErrorKey::from("name")).is_some());
+    assert!(rest.severity(&ErrorKey::from("age")).is_none());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn watch_errors_notifies_on_change_until_disposed() {
+    use leptos_dom::watch_errors;
+    use leptos_reactive::run_scope_undisposed;
+
+    let (rx, _, disposer) = run_scope_undisposed(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        let mut watched = watch_errors(cx, errors);
+        assert!(!watched.has_changed().unwrap());
+
+        errors.update(|errors| {
+            errors.insert(ErrorKey::from("a"), FieldError("email"));
+        });
+        assert!(watched.has_changed().unwrap());
+        assert!(!watched.borrow_and_update().is_empty());
+        watched
+    });
+    disposer.dispose();
+
+    // the sender is dropped along with the scope
+    assert!(rx.has_changed().is_err());
+}
+
+#[cfg(feature = "tokio")]
+#[test]
+fn error_report_stream_emits_added_errors() {
+    use futures::{executor::block_on, StreamExt};
+    use leptos_dom::error_report_stream;
+    use leptos_reactive::run_scope_undisposed;
+
+    let ((mut stream, errors), _, disposer) =
+        run_scope_undisposed(create_runtime(), |cx| {
+            let errors = create_rw_signal(cx, Errors::default());
+            errors.update(|errors| {
+                errors.insert(ErrorKey::from("before"), FieldError("name"));
+            });
+            (Box::pin(error_report_stream(cx, errors)), errors)
+        });
+
+    errors.update(|errors| {
+        errors.insert(ErrorKey::from("a"), FieldError("email"));
+    });
+    let report = block_on(stream.next()).unwrap();
+    assert_eq!(report.key, ErrorKey::from("a"));
+    assert_eq!(report.message, "invalid field: email");
+
+    // both errors of a single change are emitted
+    errors.update(|errors| {
+        errors.remove(&ErrorKey::from("a"));
+        errors.insert(ErrorKey::from("b"), FieldError("age"));
+        errors.insert(ErrorKey::from("c"), FieldError("zip"));
+    });
+    let keys = block_on(stream.by_ref().take(2).collect::<Vec<_>>())
+        .into_iter()
+        .map(|report| report.key)
+        .collect::<Vec<_>>();
+    assert_eq!(keys, [ErrorKey::from("b"), ErrorKey::from("c")]);
+
+    disposer.dispose();
+    assert!(block_on(stream.next()).is_none());
+}
+
+#[derive(Debug)]
+enum FetchError {
+    Timeout,
+    NotFound,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Timeout => write!(f, "request timed out"),
+            Self::NotFound => write!(f, "not found"),
+        }
+    }
+}
+
+impl Error for FetchError {}
+
+impl Retryable for FetchError {
+    fn is_transient(&self) -> bool {
+        matches!(self, Self::Timeout)
+    }
+}
+
+#[test]
+fn retryable_errors_are_classified_at_insert() {
+    let mut errors = Errors::default();
+    errors.insert_retryable(ErrorKey::from("timeout"), FetchError::Timeout);
+    errors.insert_retryable(ErrorKey::from("missing"), FetchError::NotFound);
+    errors.insert(ErrorKey::from("field"), FieldError("email"));
+
+    assert_eq!(
+        errors.kind(&ErrorKey::from("timeout")),
+        Some(ErrorKind::Transient)
+    );
+    assert_eq!(
+        errors.kind(&ErrorKey::from("missing")),
+        Some(ErrorKind::Permanent)
+    );
+    assert_eq!(
+        errors.kind(&ErrorKey::from("field")),
+        Some(ErrorKind::Permanent)
+    );
+
+    let transient = errors.transient().map(|(key, _)| key).collect::<Vec<_>>();
+    assert_eq!(transient, vec![&ErrorKey::from("timeout")]);
+}
+
+#[test]
+fn insert_with_kind_overrides_the_default() {
+    let mut errors = Errors::default();
+    errors.insert_with_kind(
+        ErrorKey::from("field"),
+        FieldError("email"),
+        ErrorKind::Transient,
+    );
+    assert_eq!(errors.transient().count(), 1);
+}
+
+#[test]
+fn default_error_fallback_renders_accessible_list() {
+    _ = create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        errors.update(|errors| {
+            errors.insert(ErrorKey::from("field"), FieldError("email"));
+        });
+
+        let html = default_error_fallback(cx, errors).render_to_string(cx);
+        assert!(html.contains(r#"role="alert""#));
+        assert!(html.contains(r#"role="list""#));
+        assert!(html.contains(r#"data-error-key="field""#));
+        assert!(html.contains(r#"data-error-severity="error""#));
+        assert!(html.contains("invalid field: email"));
+    });
+}
+
+#[test]
+fn error_keys_are_ordered_lexicographically() {
+    let mut state = std::collections::BTreeMap::new();
+    state.insert(ErrorKey::from("b"), 2);
+    state.insert(ErrorKey::from("a:1"), 1);
+    state.insert(ErrorKey::from("a"), 0);
+
+    let keys = state.keys().map(ErrorKey::as_str).collect::<Vec<_>>();
+    assert_eq!(keys, vec!["a", "a:1", "b"]);
+}
+
+#[test]
+#[should_panic(expected = "expected no errors, found 2:\n  a: invalid field: \
+                           email\n  b: request timed out")]
+fn assert_empty_lists_the_offending_errors() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("b"), FetchError::Timeout);
+    errors.insert(ErrorKey::from("a"), FieldError("email"));
+    errors.assert_empty();
+}
+
+#[test]
+fn assert_no_errors_passes_without_errors() {
+    create_scope(create_runtime(), |cx| {
+        assert_no_errors(cx);
+        provide_context(cx, create_rw_signal(cx, Errors::default()));
+        assert_no_errors(cx);
+    })
+    .dispose();
+}
+
+#[test]
+#[should_panic(expected = "invalid field: email")]
+fn assert_no_errors_panics_on_collected_errors() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        assert_no_errors(cx);
+    })
+    .dispose();
+}
+
+#[test]
+fn warnings_do_not_block() {
+    let mut errors = Errors::default();
+    assert!(!errors.has_blocking());
+
+    errors.insert_with_severity(
+        ErrorKey::from("hint"),
+        FieldError("nickname"),
+        ErrorSeverity::Warning,
+    );
+    assert!(!errors.is_empty());
+    assert!(!errors.has_blocking());
+
+    errors.insert(ErrorKey::from("field"), FieldError("email"));
+    assert!(errors.has_blocking());
+
+    errors.remove(&ErrorKey::from("field"));
+    assert!(!errors.has_blocking());
+}
+
+#[test]
+fn critical_errors_block() {
+    let mut errors = Errors::default();
+    errors.insert_with_severity(
+        ErrorKey::from("db"),
+        FieldError("connection"),
+        ErrorSeverity::Critical,
+    );
+    assert!(errors.has_blocking());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn registered_error_types_round_trip_through_json() {
+    use leptos_dom::{ErrorRegistry, ErrorReport, ServerError};
+
+    let mut registry = ErrorRegistry::new();
+    registry.register::<ServerTimeout>("timeout", |message| {
+        Arc::new(ServerTimeout(message))
+    });
+
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), ServerTimeout("took 30s".into()));
+    errors.insert(ErrorKey::from("b"), FieldError("email"));
+
+    let json = serde_json::to_string(&registry.to_reports(&errors)).unwrap();
+    let reports: Vec<ErrorReport> = serde_json::from_str(&json).unwrap();
+    let errors = registry.to_errors(reports);
+
+    let mut errors = errors.iter().collect::<Vec<_>>();
+    errors.sort_by_key(|(key, _)| *key);
+    let timeout = errors[0].1.downcast_ref::<ServerTimeout>().unwrap();
+    assert_eq!(timeout.0, "took 30s");
+    // unregistered types fall back to `ServerError`
+    let fallback = errors[1].1.downcast_ref::<ServerError>().unwrap();
+    assert_eq!(fallback.message(), "invalid field: email");
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+struct ServerTimeout(String);
+
+#[cfg(feature = "serde")]
+impl fmt::Display for ServerTimeout {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Error for ServerTimeout {}
+
+#[test]
+fn replaced_errors_are_not_tagged_with_their_old_type() {
+    use leptos_dom::{ErrorRegistry, ServerError};
+
+    let mut registry = ErrorRegistry::new();
+    registry.register::<FieldError>("field", |_| Arc::new(FieldError("?")));
+
+    let key = ErrorKey::from("email");
+    let mut errors = Errors::default();
+    errors.insert(key.clone(), FieldError("email"));
+    errors.entry(key.clone()).and_modify(|error| {
+        *error = Arc::new(TestError::new("replaced"));
+    });
+
+    let reports = registry.to_reports(&errors);
+    assert_eq!(reports[0].tag, None);
+    assert_eq!(reports[0].message, "replaced");
+
+    let errors = registry.to_errors(reports);
+    let (_, error) = errors.iter().next().unwrap();
+    assert_eq!(
+        error.downcast_ref::<ServerError>().unwrap().message(),
+        "replaced"
+    );
+}
+
+#[test]
+fn render_each_applies_the_formatter_chain() {
+    _ = create_scope(create_runtime(), |cx| {
+        provide_error_formatter(cx, |_, _| None);
+        provide_error_formatter(cx, |cx, error| {
+            error.downcast_ref::<FieldError>().map(|field| {
+                leptos_dom::html::strong(cx)
+                    .child(format!("Check {}", field.0))
+                    .into_view(cx)
+            })
+        });
+
+        let mut errors = Errors::default();
+        errors.insert(ErrorKey::from("field"), FieldError("email"));
+        errors.insert(ErrorKey::from("fetch"), FetchError::NotFound);
+
+        let mut views = errors
+            .render_each(cx)
+            .map(|(key, view)| (key, view.render_to_string(cx).to_string()))
+            .collect::<Vec<_>>();
+        views.sort();
+
+        assert_eq!(views[0].0, ErrorKey::from("fetch"));
+        assert!(views[0].1.contains("not found"));
+        assert!(!views[0].1.contains("<strong"));
+        assert_eq!(views[1].0, ErrorKey::from("field"));
+        assert!(views[1].1.contains("<strong"));
+        assert!(views[1].1.contains("Check email"));
+    });
+}
+
+#[derive(Debug)]
+struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("request cancelled")
+    }
+}
+
+impl Error for Cancelled {}
+
+#[test]
+fn error_filter_suppresses_matching_errors() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        provide_error_filter(cx, |error| error.is::<Cancelled>());
+
+        report_error(cx, ErrorKey::from("cancelled"), Cancelled);
+        let view: Result<(), _> = Err(Cancelled);
+        _ = view.into_view(cx);
+        assert!(errors.with(Errors::is_empty));
+
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        assert!(errors.with(|errors| {
+            errors.severity(&ErrorKey::from("field")).is_some()
+        }));
+    })
+    .dispose();
+}
+
+#[test]
+fn clear_errors_on_clears_when_the_trigger_changes() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let route = create_rw_signal(cx, "/users");
+        clear_errors_on(cx, route.into());
+
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        assert!(!errors.with(Errors::is_empty));
+
+        route.set("/settings");
+        assert!(errors.with(Errors::is_empty));
+    })
+    .dispose();
+}
+
+#[derive(Clone, Copy)]
+enum FormKey {
+    Email,
+    Name,
+}
+
+impl ErrorKeyEnum for FormKey {
+    fn as_key_str(&self) -> &'static str {
+        match self {
+            Self::Email => "form-email",
+            Self::Name => "form-name",
+        }
+    }
+}
+
+#[test]
+fn enum_variants_produce_distinct_keys() {
+    assert_eq!(ErrorKey::from(FormKey::Email), ErrorKey::from("form-email"));
+    assert_ne!(
+        ErrorKey::from(FormKey::Email),
+        ErrorKey::from(FormKey::Name)
+    );
+
+    let mut errors = Errors::default();
+    errors.insert(FormKey::Email.into(), FieldError("email"));
+    errors.insert(FormKey::Name.into(), FieldError("name"));
+    assert_eq!(errors.iter().count(), 2);
+}
+
+#[test]
+fn modify_reports_whether_anything_changed() {
+    let mut errors = Errors::default();
+    let key = ErrorKey::from("field");
+
+    let (removed, changed) = errors.modify(|errors| errors.remove(&key));
+    assert!(removed.is_none());
+    assert!(!changed);
+
+    let (_, changed) =
+        errors.modify(|errors| errors.insert(key.clone(), FieldError("email")));
+    assert!(changed);
+
+    // replacing an error keeps the length, but still counts as a change
+    let (_, changed) =
+        errors.modify(|errors| errors.insert(key.clone(), FieldError("name")));
+    assert!(changed);
+
+    let (is_empty, changed) = errors.modify(|errors| errors.is_empty());
+    assert!(!is_empty);
+    assert!(!changed);
+}
+
+#[test]
+fn modify_errors_only_notifies_on_change() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        let runs = create_rw_signal(cx, 0);
+        create_isomorphic_effect(cx, move |_| {
+            errors.track();
+            runs.update_untracked(|runs| *runs += 1);
+        });
+        assert_eq!(runs.get_untracked(), 1);
+
+        modify_errors(errors, |errors| {
+            errors.remove(&ErrorKey::from("field"));
+        });
+        assert_eq!(runs.get_untracked(), 1);
+
+        modify_errors(errors, |errors| {
+            errors.insert(ErrorKey::from("field"), FieldError("email"));
+        });
+        assert_eq!(runs.get_untracked(), 2);
+    })
+    .dispose();
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn par_iter_visits_every_error() {
+    use rayon::iter::ParallelIterator;
+
+    let mut errors = Errors::default();
+    for i in 0..100 {
+        errors.insert(ErrorKey::from(i.to_string()), TestError::new("failed"));
+    }
+
+    let mut keys = errors
+        .par_iter()
+        .map(|(key, error)| {
+            assert_eq!(error.to_string(), "failed");
+            key.as_str().parse::<usize>().unwrap()
+        })
+        .collect::<Vec<_>>();
+    keys.sort_unstable();
+    assert_eq!(keys, (0..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn reserved_keys_keep_their_order() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("other"), FetchError::NotFound);
+    errors.reserve_keys([
+        ErrorKey::from("email"),
+        ErrorKey::from("name"),
+        ErrorKey::from("password"),
+    ]);
+    assert_eq!(errors.len(), 1);
+
+    errors.insert(ErrorKey::from("password"), FieldError("password"));
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert(ErrorKey::from("late"), FetchError::Timeout);
+    let keys = |errors: &Errors| {
+        errors
+            .iter()
+            .map(|(key, _)| key.as_str().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&errors), ["email", "password", "other", "late"]);
+
+    // replacing or removing an error doesn't move the others
+    errors.insert(ErrorKey::from("email"), FieldError("email again"));
+    errors.remove(&ErrorKey::from("password"));
+    errors.insert(ErrorKey::from("name"), FieldError("name"));
+    assert_eq!(keys(&errors), ["email", "name", "other", "late"]);
+    assert_eq!(errors.len(), 4);
+}
+
+#[test]
+fn reserved_keys_are_not_counted() {
+    let mut errors = Errors::default();
+    errors.reserve_keys([ErrorKey::from("email")]);
+    assert!(errors.is_empty());
+    assert_eq!(errors.len(), 0);
+    assert_eq!(errors.iter().count(), 0);
+
+    errors
+        .entry(ErrorKey::from("email"))
+        .or_insert(FieldError("email"));
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn entry_places_errors_for_reserved_keys() {
+    let mut errors = Errors::default();
+    errors.reserve_keys([ErrorKey::from("email"), ErrorKey::from("name")]);
+    errors.insert(ErrorKey::from("other"), FetchError::NotFound);
+
+    let error = errors
+        .entry(ErrorKey::from("name"))
+        .or_insert(FieldError("name"));
+    assert_eq!(error.to_string(), "invalid field: name");
+    let error = errors
+        .entry(ErrorKey::from("email"))
+        .or_insert(FieldError("email"));
+    assert_eq!(error.to_string(), "invalid field: email");
+    let error = errors
+        .entry(ErrorKey::from("name"))
+        .or_insert(FieldError("ignored"));
+    assert_eq!(error.to_string(), "invalid field: name");
+
+    let keys = errors
+        .iter()
+        .map(|(key, _)| key.as_str().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["email", "name", "other"]);
+}
+
+#[test]
+fn into_view_or_renders_the_inline_fallback() {
+    create_scope(create_runtime(), |cx| {
+        let ok: Result<&str, FieldError> = Ok("hello");
+        let html = ok.into_view_or(cx, || "fallback").render_to_string(cx);
+        assert!(html.contains("hello"));
+        assert!(!html.contains("fallback"));
+
+        // without a boundary
+        let err: Result<&str, _> = Err(FieldError("email"));
+        let html = err.into_view_or(cx, || "fallback").render_to_string(cx);
+        assert!(html.contains("fallback"));
+
+        // with a boundary, the error is still reported
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let err: Result<&str, _> = Err(FieldError("email"));
+        let html = err.into_view_or(cx, || "fallback").render_to_string(cx);
+        assert!(html.contains("fallback"));
+        assert_eq!(errors.with(Errors::len), 1);
+    })
+    .dispose();
+}
+
+#[test]
+fn insert_result_only_inserts_errors() {
+    let mut errors = Errors::default();
+
+    let age = errors.insert_result(ErrorKey::from("age"), "42".parse::<u8>());
+    assert_eq!(age, Some(42));
+    assert!(errors.is_empty());
+
+    let age = errors.insert_result(ErrorKey::from("age"), "-1".parse::<u8>());
+    assert_eq!(age, None);
+    assert!(errors.user_message(&ErrorKey::from("age")).is_some());
+}
+
+#[derive(Debug)]
+struct Blank {
+    // only read through `Debug`
+    #[allow(dead_code)]
+    code: u16,
+}
+
+impl fmt::Display for Blank {
+    fn fmt(&self, _: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Ok(())
+    }
+}
+
+impl Error for Blank {}
+
+impl DisplayHint for Blank {
+    fn multiline(&self) -> bool {
+        true
+    }
+}
+
+impl UserFacing for Blank {
+    fn user_message(&self) -> String {
+        String::new()
+    }
+}
+
+#[test]
+fn empty_display_falls_back_to_debug() {
+    _ = create_scope(create_runtime(), |cx| {
+        let key = ErrorKey::from("blank");
+        let render = |errors: &Errors| {
+            errors_view(cx, errors, &ErrorViewOptions::default())
+                .render_to_string(cx)
+        };
+
+        let mut errors = Errors::default();
+        errors.insert(key.clone(), Blank { code: 503 });
+        assert!(render(&errors).contains("Blank { code: 503 }"));
+
+        // as a multiline `<pre>` block
+        errors.insert_with_hint(key.clone(), Blank { code: 504 });
+        let html = render(&errors);
+        assert!(html.contains("<pre"));
+        assert!(html.contains("Blank { code: 504 }"));
+
+        // with an empty user-facing message
+        errors.insert_user_facing(key.clone(), Blank { code: 505 });
+        assert!(render(&errors).contains("Blank { code: 505 }"));
+        assert_eq!(
+            errors.user_message(&key).as_deref(),
+            Some("Blank { code: 505 }")
+        );
+    });
+}
+
+#[derive(Clone, Default)]
+struct RecordingSink(Arc<std::sync::Mutex<Vec<String>>>);
+
+impl ErrorSink for RecordingSink {
+    fn report(&self, key: ErrorKey, error: Arc<dyn Error + Send + Sync>) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("report {}: {error}", key.as_str()));
+    }
+
+    fn remove(&self, key: &ErrorKey) {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("remove {}", key.as_str()));
+    }
+}
+
+#[test]
+fn custom_error_sink_collects_errors_without_boundary() {
+    let sink = RecordingSink::default();
+    let recorded = sink.clone();
+    create_scope(create_runtime(), move |cx| {
+        provide_error_sink(cx, sink);
+        assert!(has_error_handler(cx));
+        assert!(!has_error_boundary(cx));
+
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        _ = Ok::<_, FieldError>("fine").into_view(cx);
+    })
+    .dispose();
+
+    let records = recorded.0.lock().unwrap();
+    assert_eq!(records[0], "report field: invalid field: email");
+    assert!(records[1].starts_with("remove "));
+}
+
+#[test]
+fn sink_errors_are_removed_when_their_scope_is_disposed() {
+    let sink = RecordingSink::default();
+    let recorded = sink.clone();
+    create_scope(create_runtime(), move |cx| {
+        provide_error_sink(cx, sink);
+
+        let (_, first) = cx.run_child_scope(|cx| {
+            report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        });
+        // a newer error under the same key outlives the older one
+        let (_, second) = cx.run_child_scope(|cx| {
+            report_error(cx, ErrorKey::from("field"), FieldError("name"));
+        });
+        first.dispose();
+        assert_eq!(recorded.0.lock().unwrap().len(), 2);
+
+        second.dispose();
+        assert_eq!(
+            *recorded.0.lock().unwrap(),
+            [
+                "report field: invalid field: email",
+                "report field: invalid field: name",
+                "remove field",
+            ]
+        );
+    })
+    .dispose();
+}
+
+#[test]
+fn error_boundary_takes_precedence_over_sink() {
+    let sink = RecordingSink::default();
+    let recorded = sink.clone();
+    create_scope(create_runtime(), move |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_error_sink(cx, sink);
+        provide_context(cx, errors);
+
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        assert_eq!(errors.with(Errors::len), 1);
+    })
+    .dispose();
+
+    assert!(recorded.0.lock().unwrap().is_empty());
+}
+
+#[test]
+fn channel_sink_collects_errors_headlessly() {
+    let (sender, receiver) = std::sync::mpsc::channel();
+    create_scope(create_runtime(), move |cx| {
+        provide_error_sink(cx, sender);
+
+        _ = Err::<(), _>(FetchError::Timeout).into_view(cx);
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+    })
+    .dispose();
+
+    let collected = receiver
+        .try_iter()
+        .map(|(key, error)| (key.as_str().to_string(), error.to_string()))
+        .collect::<Vec<_>>();
+    assert_eq!(collected.len(), 2);
+    assert_eq!(collected[0].1, "request timed out");
+    assert_eq!(
+        collected[1],
+        ("field".to_string(), "invalid field: email".to_string())
+    );
+}
+
+#[test]
+fn contains_type_finds_matching_errors() {
+    let mut errors = Errors::default();
+    assert!(!errors.contains_type::<FieldError>());
+
+    errors.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+    assert!(!errors.contains_type::<FieldError>());
+    assert!(errors.contains_type::<FetchError>());
+
+    errors.insert(ErrorKey::from("field"), FieldError("email"));
+    assert!(errors.contains_type::<FieldError>());
+}
+
+/// Renders an `Err` inside an anchor, after `before` components have been
+/// rendered outside of it, and returns the key of the error.
+fn key_inside_anchor(before: usize) -> ErrorKey {
+    HydrationCtx::continue_from(HydrationKey {
+        previous: format!("0-{before}-"),
+        offset: 0,
+    });
+    let (key, _, disposer) =
+        leptos_reactive::run_scope_undisposed(create_runtime(), |cx| {
+            let errors = create_rw_signal(cx, Errors::default());
+            provide_context(cx, errors);
+            provide_error_key_anchor(cx, "profile");
+
+            // a component within the anchor
+            HydrationCtx::next_component();
+            _ = Err::<(), _>(FieldError("email")).into_view(cx);
+
+            errors.with(|errors| errors.iter().next().unwrap().0.clone())
+        });
+    disposer.dispose();
+    key
+}
+
+#[test]
+fn anchored_keys_ignore_components_before_the_anchor() {
+    // e.g. a `<Show/>` before the anchor renders on the server, but not yet
+    // on the client
+    let server = key_inside_anchor(3);
+    let client = key_inside_anchor(2);
+    assert_eq!(server, client);
+    assert_eq!(server.as_str(), "profile@0-");
+}
+
+#[test]
+fn report_error_async_reports_from_a_spawned_future() {
+    use futures::{channel::oneshot, executor::LocalPool, task::LocalSpawnExt};
+
+    // `spawn_local` needs a tokio `LocalSet` with `ssr`, so drive the future
+    // with an executor of our own to run it the same way with every feature
+    let mut pool = LocalPool::new();
+    create_scope(create_runtime(), move |cx| {
+        assert!(use_errors(cx).is_none());
+        let boundary = create_rw_signal(cx, Errors::default());
+        provide_context(cx, boundary);
+
+        let errors = use_errors(cx).unwrap();
+        let (tx, rx) = oneshot::channel::<Result<(), FetchError>>();
+        pool.spawner()
+            .spawn_local(async move {
+                if let Ok(Err(error)) = rx.await {
+                    report_error_async(errors, ErrorKey::from("save"), error);
+                }
+            })
+            .unwrap();
+
+        pool.run_until_stalled();
+        assert!(boundary.with(Errors::is_empty));
+
+        tx.send(Err(FetchError::Timeout)).unwrap();
+        pool.run_until_stalled();
+        assert!(boundary.with(|errors| errors.contains_type::<FetchError>()));
+    })
+    .dispose();
+}
+
+#[test]
+fn render_results_reports_each_error_by_position() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let results = ["1", "x", "3", "y"]
+            .into_iter()
+            .map(|s| s.parse::<u8>().map(|n| format!("item {n}")));
+        let html = render_results(cx, results).render_to_string(cx);
+        assert!(html.contains("item 1"));
+        assert!(html.contains("item 3"));
+
+        let keys = errors.with(|errors| {
+            errors
+                .iter()
+                .map(|(key, _)| key.as_str().to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(keys.len(), 2);
+        assert!(keys[0].ends_with("#1"));
+        assert!(keys[1].ends_with("#3"));
+    })
+    .dispose();
+}
+
+#[test]
+fn find_returns_the_first_match_in_order() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), FetchError::Timeout);
+    errors.insert(ErrorKey::from("b"), FieldError("email"));
+    errors.insert(ErrorKey::from("c"), FieldError("name"));
+
+    let (key, error) = errors
+        .find(|_, error| error.to_string().contains("invalid field"))
+        .unwrap();
+    assert_eq!(key, &ErrorKey::from("b"));
+    assert_eq!(error.to_string(), "invalid field: email");
+
+    assert!(errors.find(|_, error| error.is::<Cancelled>()).is_none());
+}
+
+#[cfg(feature = "tracing-error")]
+#[test]
+fn span_trace_is_captured_on_insert() {
+    use tracing_error::ErrorLayer;
+    use tracing_subscriber::prelude::*;
+
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("outside"), Cancelled);
+
+    let subscriber = tracing_subscriber::registry().with(ErrorLayer::default());
+    tracing::subscriber::with_default(subscriber, || {
+        let _span = tracing::info_span!("load_user", id = 7).entered();
+        errors.insert(ErrorKey::from("inside"), FetchError::NotFound);
+    });
+
+    assert!(errors.span_trace(&ErrorKey::from("outside")).is_none());
+    let trace = errors.span_trace(&ErrorKey::from("inside")).unwrap();
+    assert!(trace.to_string().contains("load_user"));
+
+    let reports = errors.to_reports();
+    assert!(reports[0].span_trace.is_none());
+    assert!(reports[1].span_trace.as_ref().unwrap().contains("id=7"));
+}
+
+#[test]
+fn insert_batch_shares_metadata() {
+    let mut errors = Errors::default();
+    let meta = HashMap::from([("request".to_string(), "42".to_string())]);
+    errors.insert_batch(
+        [
+            (ErrorKey::from("email"), FieldError("email")),
+            (ErrorKey::from("name"), FieldError("name")),
+        ],
+        meta,
+    );
+    errors.insert(ErrorKey::from("other"), FieldError("age"));
+
+    assert_eq!(errors.len(), 3);
+    let email = errors.metadata(&ErrorKey::from("email")).unwrap();
+    let name = errors.metadata(&ErrorKey::from("name")).unwrap();
+    assert!(Arc::ptr_eq(email, name));
+    assert_eq!(email.get("request").map(String::as_str), Some("42"));
+    assert!(errors.metadata(&ErrorKey::from("other")).is_none());
+}
+
+#[test]
+fn since_returns_errors_added_after_a_version() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("a"), FieldError("a"));
+    errors.insert(ErrorKey::from("b"), FieldError("b"));
+
+    // first frame: everything is new
+    let (added, version) = errors.since(0);
+    assert_eq!(added.len(), 2);
+
+    errors.remove(&ErrorKey::from("a"));
+    errors.insert(ErrorKey::from("c"), FieldError("c"));
+    errors.insert(ErrorKey::from("b"), FieldError("b2"));
+
+    // second frame: only what was inserted after the first frame
+    let (added, next) = errors.since(version);
+    let keys = added
+        .iter()
+        .map(|(key, _)| key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(keys, ["b", "c"]);
+    assert!(next > version);
+    assert!(errors.since(next).0.is_empty());
+}
+
+#[test]
+fn nested_results_get_distinct_stable_keys() {
+    type Nested = Result<Result<&'static str, FieldError>, FieldError>;
+
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let render = |result: Nested| {
+            HydrationCtx::continue_from(HydrationKey {
+                previous: "0-0-".to_string(),
+                offset: 0,
+            });
+            _ = result.into_view(cx);
+            errors.with(|errors| {
+                errors
+                    .iter()
+                    .map(|(key, _)| key.clone())
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        let inner = render(Ok(Err(FieldError("inner"))));
+        assert_eq!(inner.len(), 1);
+
+        // the outer error doesn't replace the inner one
+        let both = render(Err(FieldError("outer")));
+        assert_eq!(both.len(), 2);
+        assert_eq!(both[0], inner[0]);
+        let outer = both[1].clone();
+        assert_eq!(inner[0].as_str(), format!("{}/1", outer.as_str()));
+
+        // rendering the inner error again clears the outer one, and keeps
+        // its key
+        assert_eq!(render(Ok(Err(FieldError("inner")))), inner);
+        assert_eq!(render(Ok(Ok("fine"))), Vec::<ErrorKey>::new());
+        assert_eq!(render(Err(FieldError("outer"))), [outer]);
+    })
+    .dispose();
+}
+
+/// An error that counts how often its message has been formatted.
+#[derive(Debug)]
+struct ExpensiveError(Arc<AtomicUsize>);
+
+impl fmt::Display for ExpensiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fetch_add(1, Ordering::Relaxed);
+        write!(f, "- old\n+ new")
+    }
+}
+
+impl Error for ExpensiveError {}
+
+#[test]
+fn insert_cached_formats_the_message_once() {
+    let formatted = Arc::new(AtomicUsize::new(0));
+    let mut errors = Errors::default();
+    errors.insert_cached(
+        ErrorKey::from("diff"),
+        ExpensiveError(Arc::clone(&formatted)),
+    );
+    assert_eq!(formatted.load(Ordering::Relaxed), 1);
+
+    for _ in 0..3 {
+        assert_eq!(
+            errors.cached_message(&ErrorKey::from("diff")),
+            Some("- old\n+ new")
+        );
+        assert_eq!(
+            errors.user_message(&ErrorKey::from("diff")).as_deref(),
+            Some("- old\n+ new")
+        );
+    }
+    assert_eq!(formatted.load(Ordering::Relaxed), 1);
+
+    errors.insert(ErrorKey::from("plain"), FieldError("email"));
+    assert!(errors.cached_message(&ErrorKey::from("plain")).is_none());
+}
+
+#[test]
+fn views_render_cached_messages_without_formatting() {
+    let formatted = Arc::new(AtomicUsize::new(0));
+    let mut errors = Errors::default();
+    errors.insert_cached(
+        ErrorKey::from("billing:diff"),
+        ExpensiveError(Arc::clone(&formatted)),
+    );
+    assert_eq!(formatted.load(Ordering::Relaxed), 1);
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let options = ErrorViewOptions::default();
+        let html = errors_view(cx, &errors, &options).render_to_string(cx);
+        assert!(html.contains("- old\n+ new"));
+        _ = grouped_errors_view(cx, &errors, &options, str::to_string)
+            .render_to_string(cx);
+        for (_, view) in errors.render_each(cx) {
+            _ = view.render_to_string(cx);
+        }
+        let signal = create_rw_signal(cx, errors.clone());
+        _ = default_error_fallback(cx, signal).render_to_string(cx);
+        _ = errors.to_reports();
+    });
+    assert_eq!(formatted.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn replacing_an_error_through_entry_drops_its_cached_message() {
+    let formatted = Arc::new(AtomicUsize::new(0));
+    let key = ErrorKey::from("diff");
+    let mut errors = Errors::default();
+    errors.insert_cached(key.clone(), ExpensiveError(Arc::clone(&formatted)));
+    errors.entry(key.clone()).and_modify(|error| {
+        *error = Arc::new(FieldError("email"));
+    });
+
+    assert!(errors.cached_message(&key).is_none());
+    assert_eq!(
+        errors.user_message(&key).as_deref(),
+        Some("invalid field: email")
+    );
+
+    errors.insert_cached(key.clone(), ExpensiveError(Arc::clone(&formatted)));
+    *errors.entry(key.clone()).or_insert(FieldError("unused")) =
+        Arc::new(FieldError("name"));
+    assert_eq!(
+        errors.user_message(&key).as_deref(),
+        Some("invalid field: name")
+    );
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(html.contains("invalid field: name"));
+        assert!(!html.contains("+ new"));
+    });
+}
+
+#[test]
+fn use_error_reports_only_updates_on_changes() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let unrelated = create_rw_signal(cx, 0);
+
+        let reports = use_error_reports(cx);
+        let runs = create_rw_signal(cx, 0);
+        create_isomorphic_effect(cx, move |_| {
+            reports.track();
+            runs.update_untracked(|runs| *runs += 1);
+        });
+        assert_eq!(runs.get_untracked(), 1);
+        assert!(reports.get_untracked().is_empty());
+
+        errors.update(|errors| {
+            errors.insert(ErrorKey::from("email"), FieldError("email"))
+        });
+        assert_eq!(runs.get_untracked(), 2);
+        let list = reports.get_untracked();
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].message, "invalid field: email");
+
+        // the same error again, and churn elsewhere, change no report
+        errors.update(|errors| {
+            errors.insert(ErrorKey::from("email"), FieldError("email"))
+        });
+        unrelated.set(1);
+        assert_eq!(runs.get_untracked(), 2);
+    })
+    .dispose();
+}
+
+#[test]
+fn dismiss_removes_by_id_even_if_keys_are_reused() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("0-1"), FieldError("email"));
+    errors.insert(ErrorKey::from("0-2"), FieldError("name"));
+    let ids = errors
+        .iter_with_ids()
+        .map(|(id, _, _)| id)
+        .collect::<Vec<_>>();
+    assert_eq!(ids.len(), 2);
+    assert_ne!(ids[0], ids[1]);
+
+    // a re-render reuses the first key for another error
+    errors.insert(ErrorKey::from("0-1"), FieldError("age"));
+
+    // the error that was shown is already gone, and the new one is kept
+    assert!(errors.dismiss(ids[0]).is_none());
+    assert_eq!(
+        errors.user_message(&ErrorKey::from("0-1")).as_deref(),
+        Some("invalid field: age")
+    );
+
+    let dismissed = errors.dismiss(ids[1]).unwrap();
+    assert_eq!(dismissed.to_string(), "invalid field: name");
+    assert_eq!(errors.len(), 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn extend_from_json_inserts_server_errors() {
+    use leptos_dom::ServerError;
+
+    let payload = serde_json::json!([
+        { "key": "email", "message": "already registered" },
+        { "key": "password", "message": "too short", "field": 2 },
+    ]);
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("form"), FieldError("name"));
+    errors.extend_from_json(&payload).unwrap();
+
+    assert_eq!(errors.len(), 3);
+    let email = errors.find(|key, _| key.as_str() == "email").unwrap().1;
+    assert_eq!(
+        email.downcast_ref::<ServerError>().unwrap().message(),
+        "already registered"
+    );
+    assert_eq!(
+        errors.user_message(&ErrorKey::from("password")).as_deref(),
+        Some("too short")
+    );
+
+    let invalid = serde_json::json!([{ "key": "email" }]);
+    assert!(errors.extend_from_json(&invalid).is_err());
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn suppressed_subtree_reports_no_errors() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let (_, embed) = cx.run_child_scope(|cx| {
+            suppress_errors_in_subtree(cx);
+            assert!(!has_error_boundary(cx));
+            assert!(use_errors(cx).is_none());
+
+            report_error(cx, ErrorKey::from("embed"), FieldError("email"));
+            _ = Err::<(), _>(FieldError("name")).into_view(cx);
+            _ = Err::<(), _>(Cancelled).into_view_or(cx, || "fallback");
+        });
+        assert!(errors.with(Errors::is_empty));
+
+        // the rest of the app still reports its errors
+        report_error(cx, ErrorKey::from("app"), FieldError("age"));
+        assert_eq!(errors.with(Errors::len), 1);
+        embed.dispose();
+    })
+    .dispose();
+}
+
+/// Counts the warnings logged while it is the default subscriber.
+#[derive(Clone, Default)]
+struct WarningCounter(Arc<AtomicUsize>);
+
+impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for WarningCounter {
+    fn on_event(
+        &self,
+        event: &tracing::Event<'_>,
+        _: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        if *event.metadata().level() == tracing::Level::WARN {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+// the warning is only logged in debug builds
+#[cfg(debug_assertions)]
+#[test]
+fn missing_boundary_warns_once_per_key() {
+    use tracing_subscriber::prelude::*;
+
+    let counter = WarningCounter::default();
+    let subscriber = tracing_subscriber::registry().with(counter.clone());
+    tracing::subscriber::with_default(subscriber, || {
+        create_scope(create_runtime(), |cx| {
+            for _ in 0..3 {
+                report_error(cx, ErrorKey::from("a"), FieldError("email"));
+            }
+            report_error(cx, ErrorKey::from("b"), FieldError("email"));
+        })
+        .dispose();
+    });
+    assert_eq!(counter.0.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+fn iteration_order_does_not_depend_on_the_hasher_seed() {
+    // every `Errors` gets a randomly seeded hasher
+    let build = || {
+        let mut errors = Errors::default();
+        for field in ["name", "email", "age", "street", "city", "zip"] {
+            errors.insert(ErrorKey::from(field), FieldError(field));
+        }
+        errors
+    };
+    let keys = |errors: &Errors| {
+        errors
+            .iter()
+            .map(|(key, _)| key.as_str().to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let first = keys(&build());
+    assert_eq!(first, ["name", "email", "age", "street", "city", "zip"]);
+    for _ in 0..10 {
+        assert_eq!(keys(&build()), first);
+    }
+}
+
+#[test]
+fn add_context_wraps_errors_and_keeps_their_sources() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert_with_context(
+        ErrorKey::from("fetch"),
+        FetchError::Timeout,
+        "while loading the user",
+    );
+    errors.add_context("while loading the dashboard");
+
+    let messages = errors
+        .iter()
+        .map(|(_, error)| error.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        [
+            "while loading the dashboard: invalid field: email",
+            "while loading the dashboard: while loading the user: request \
+             timed out",
+        ]
+    );
+
+    let (_, fetch) = errors.find(|key, _| key.as_str() == "fetch").unwrap();
+    let context = fetch.downcast_ref::<ContextError>().unwrap();
+    assert_eq!(context.context(), "while loading the dashboard");
+    let original = ErrorChain::new(&**fetch).last().unwrap();
+    assert!(original.is::<FetchError>());
+    assert_eq!(ErrorChain::new(&**fetch).count(), 3);
+}
+
+#[derive(Debug)]
+struct ErrorPage;
+
+impl fmt::Display for ErrorPage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("service unavailable")
+    }
+}
+
+impl Error for ErrorPage {}
+
+impl HtmlError for ErrorPage {
+    fn html(&self) -> String {
+        "<p>Service <em>unavailable</em></p>".to_string()
+    }
+}
+
+#[test]
+fn trusted_html_is_only_injected_for_html_errors() {
+    let mut errors = Errors::default();
+    errors.insert_html(ErrorKey::from("page"), ErrorPage);
+    errors.insert(ErrorKey::from("text"), TestError::new("<em>text</em>"));
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(html.contains("service unavailable"));
+        assert!(!html.contains("<em>"));
+
+        let options = ErrorViewOptions {
+            trusted_html: true,
+            ..Default::default()
+        };
+        let html = errors_view(cx, &errors, &options).render_to_string(cx);
+        assert!(html.contains("<p>Service <em>unavailable</em></p>"));
+        assert!(!html.contains("service unavailable"));
+        assert!(!html.contains("<em>text"));
+    });
+}
+
+#[test]
+fn add_context_keeps_user_messages_html_and_hints() {
+    let users = ErrorKey::from("users");
+    let page = ErrorKey::from("page");
+    let trace = ErrorKey::from("trace");
+    let mut errors = Errors::default();
+    errors.insert_user_facing(users.clone(), QueryError);
+    errors.insert_html(page, ErrorPage);
+    errors.insert_with_hint(
+        trace.clone(),
+        Diagnostic {
+            lines: vec!["first", "second"],
+            multiline: true,
+        },
+    );
+    errors.add_context("while loading the dashboard");
+    errors.add_context("while rendering");
+
+    assert_eq!(
+        errors.user_message(&users).as_deref(),
+        Some("Could not load users.")
+    );
+    assert!(errors.is_multiline(&trace));
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let options = ErrorViewOptions {
+            trusted_html: true,
+            ..Default::default()
+        };
+        let html = errors_view(cx, &errors, &options).render_to_string(cx);
+        assert!(html.contains("<p>Service <em>unavailable</em></p>"));
+    });
+}
+
+#[cfg(feature = "intern-keys")]
+#[test]
+fn interned_keys_share_their_string() {
+    use std::collections::HashSet;
+
+    let keys = (0..3)
+        .map(|_| ErrorKey::interned(&"form:email".to_string()))
+        .collect::<Vec<_>>();
+    assert!(keys
+        .windows(2)
+        .all(|pair| pair[0].as_str().as_ptr() == pair[1].as_str().as_ptr()));
+    let other = ErrorKey::interned("form:name");
+    assert_ne!(other.as_str().as_ptr(), keys[0].as_str().as_ptr());
+
+    // keys converted from owned strings are not interned
+    let owned = ErrorKey::from("form:email".to_string());
+    assert_eq!(owned, keys[0]);
+    assert_ne!(owned.as_str().as_ptr(), keys[0].as_str().as_ptr());
+
+    // interned keys are interchangeable with static ones
+    let literal = ErrorKey::from("form:email");
+    assert_eq!(keys[0], literal);
+    let set = HashSet::from([literal]);
+    assert!(set.contains(&keys[1]));
+
+    let mut errors = Errors::default();
+    errors.insert(keys[0].clone(), FieldError("email"));
+    errors.insert(ErrorKey::from_static("form:email"), FieldError("email"));
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn grouped_errors_view_renders_a_section_per_namespace() {
+    let shipping = ErrorKeyPrefix::new("shipping");
+    let billing = ErrorKeyPrefix::new("billing");
+    let mut errors = Errors::default();
+    errors.insert(shipping.key("0-1"), FieldError("street"));
+    errors.insert(billing.key("0-2"), FieldError("card"));
+    errors.insert(shipping.key("0-3"), FieldError("zip"));
+    errors.insert(ErrorKey::from("form"), FieldError("terms"));
+    assert_eq!(shipping.key("0-1").namespace(), Some("shipping"));
+    assert_eq!(ErrorKey::from("form").namespace(), None);
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let html = grouped_errors_view(
+            cx,
+            &errors,
+            &ErrorViewOptions::default(),
+            |namespace| format!("{namespace} address"),
+        )
+        .render_to_string(cx);
+
+        assert_eq!(html.matches("<section").count(), 2);
+        let shipping = html.find("shipping address</h3>").unwrap();
+        let billing = html.find("billing address</h3>").unwrap();
+        assert!(shipping < billing);
+        // both shipping errors are in the first section
+        let street = html.find("invalid field: street").unwrap();
+        let zip = html.find("invalid field: zip").unwrap();
+        assert!(shipping < street && street < zip && zip < billing);
+        // the terms error comes last, outside of the sections
+        let terms = html.find("invalid field: terms").unwrap();
+        assert!(terms > html.rfind("</section>").unwrap());
+    });
+}
+
+#[test]
+fn insert_counting_returns_the_total() {
+    let mut errors = Errors::default();
+    assert_eq!(errors.insert_counting(ErrorKey::from("a"), Cancelled), 1);
+    assert_eq!(errors.insert_counting(ErrorKey::from("b"), Cancelled), 2);
+    // replacing an error doesn't add to the count
+    assert_eq!(errors.insert_counting(ErrorKey::from("a"), Cancelled), 2);
+    assert_eq!(errors.insert_counting(ErrorKey::from("c"), Cancelled), 3);
+}
+
+/// An error that is its own source.
+#[derive(Debug)]
+struct CyclicError;
+
+impl fmt::Display for CyclicError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("cyclic")
+    }
+}
+
+impl Error for CyclicError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&CyclicError)
+    }
+}
+
+#[test]
+fn iter_with_depth_counts_sources() {
+    let root = TestError::new("connection refused");
+    let middle = TestError::new("query failed").caused_by(root);
+    let top = TestError::new("failed to load").caused_by(middle);
+
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("chain"), top);
+    errors.insert(ErrorKey::from("single"), Cancelled);
+    errors.insert(ErrorKey::from("cycle"), CyclicError);
+
+    let depths = errors
+        .iter_with_depth()
+        .map(|(key, _, depth)| (key.as_str(), depth))
+        .collect::<Vec<_>>();
+    assert_eq!(depths, [("chain", 2), ("single", 0), ("cycle", 32)]);
+}
+
+#[test]
+fn acknowledged_errors_are_kept_but_not_counted() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert(ErrorKey::from("name"), FieldError("name"));
+    assert!(errors.acknowledge(&ErrorKey::from("email")));
+    assert!(!errors.acknowledge(&ErrorKey::from("age")));
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors.is_acknowledged(&ErrorKey::from("email")));
+    let unseen = errors
+        .iter_unacknowledged()
+        .map(|(key, _)| key.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(unseen, ["name"]);
+
+    // errors merged in for the same key are still acknowledged
+    let mut fresh = Errors::default();
+    fresh.insert(ErrorKey::from("email"), FieldError("email"));
+    fresh.insert(ErrorKey::from("age"), FieldError("age"));
+    errors.merge(fresh);
+    assert_eq!(errors.len(), 3);
+    assert!(errors.is_acknowledged(&ErrorKey::from("email")));
+    assert!(!errors.is_acknowledged(&ErrorKey::from("age")));
+    assert_eq!(errors.iter_unacknowledged().count(), 2);
+}
+
+#[test]
+fn errors_are_cleaned_up_when_their_scope_is_disposed() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        // on the server, the error is removed as soon as the scope is gone
+        let (_, child) = cx.run_child_scope(|cx| {
+            report_error(cx, ErrorKey::from("a"), FieldError("email"));
+        });
+        assert_eq!(errors.with(Errors::len), 1);
+        child.dispose();
+        assert!(errors.with(Errors::is_empty));
+    })
+    .dispose();
+}
+
+#[test]
+fn cleanup_error_keeps_newer_errors_under_the_same_key() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        let key = ErrorKey::from("a");
+        let id_of = |errors: &Errors| errors.iter_with_ids().next().unwrap().0;
+
+        // cleaning up before the re-render inserts its error
+        errors.update(|errors| errors.insert(key.clone(), FieldError("old")));
+        let old = errors.with(id_of);
+        assert!(cleanup_error(errors, &key, old));
+        assert!(errors.with(Errors::is_empty));
+
+        // cleaning up in a microtask, after the re-render inserted its error
+        errors.update(|errors| errors.insert(key.clone(), FieldError("old")));
+        let old = errors.with(id_of);
+        errors.update(|errors| errors.insert(key.clone(), FieldError("new")));
+        assert!(!cleanup_error(errors, &key, old));
+        assert_eq!(
+            errors.with(|errors| errors.user_message(&key)).as_deref(),
+            Some("invalid field: new")
+        );
+    })
+    .dispose();
+}
+
+#[test]
+fn key_strategy_generates_result_keys() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let next = std::cell::Cell::new(0);
+        provide_key_strategy(cx, move |_| {
+            let key = ErrorKey::from(format!("error-{}", next.get()));
+            next.set(next.get() + 1);
+            key
+        });
+
+        for field in ["email", "name", "age"] {
+            _ = Err::<(), _>(FieldError(field)).into_view(cx);
+        }
+        let keys = errors.with(|errors| {
+            errors
+                .iter()
+                .map(|(key, _)| key.as_str().to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(keys, ["error-0", "error-1", "error-2"]);
+
+        // a prefix still applies
+        let (_, child) = cx.run_child_scope(|cx| {
+            provide_error_key_prefix(cx, "form");
+            _ = Err::<(), _>(Cancelled).into_view(cx);
+        });
+        assert!(errors.with(|errors| {
+            errors.iter().any(|(key, _)| key.as_str() == "form:error-3")
+        }));
+        child.dispose();
+    })
+    .dispose();
+}
+
+#[test]
+fn result_of_option_renders_all_three_states() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        let render = |result: Result<Option<&'static str>, FieldError>| {
+            HydrationCtx::continue_from(HydrationKey {
+                previous: "0-0-".to_string(),
+                offset: 0,
+            });
+            result.into_view(cx).render_to_string(cx).to_string()
+        };
+
+        assert!(render(Ok(Some("Ada"))).contains("Ada"));
+        assert!(errors.with(Errors::is_empty));
+
+        render(Err(FieldError("user")));
+        assert_eq!(errors.with(Errors::len), 1);
+
+        // loaded, but absent: renders nothing, and clears the error
+        assert!(!render(Ok(None)).contains("Ada"));
+        assert!(errors.with(Errors::is_empty));
+    })
+    .dispose();
+}
+
+#[test]
+fn error_enricher_wraps_errors_after_filtering() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        provide_error_filter(cx, |error| error.is::<Cancelled>());
+        provide_error_enricher(cx, |error| {
+            Arc::new(ContextError::new("request 42", error))
+        });
+
+        report_error(cx, ErrorKey::from("cancelled"), Cancelled);
+        report_error(cx, ErrorKey::from("field"), FieldError("email"));
+        _ = Err::<(), _>(FetchError::Timeout).into_view(cx);
+
+        let messages = errors.with(|errors| {
+            errors
+                .iter()
+                .map(|(_, error)| error.to_string())
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            messages,
+            [
+                "request 42: invalid field: email",
+                "request 42: request timed out"
+            ]
+        );
+        assert!(errors.with(|errors| {
+            errors.iter().all(|(_, error)| error.is::<ContextError>())
+        }));
+    })
+    .dispose();
+}
+
+#[test]
+fn split_partitions_errors_by_severity() {
+    let mut errors = Errors::default();
+    errors.insert_with_severity(
+        ErrorKey::from("email"),
+        TestError::new("invalid email"),
+        ErrorSeverity::Warning,
+    );
+    errors.insert(ErrorKey::from("save"), TestError::new("could not save"));
+    errors.insert_with_severity(
+        ErrorKey::from("name"),
+        TestError::new("name is short"),
+        ErrorSeverity::Warning,
+    );
+
+    let severities = errors.clone();
+    let (warnings, blocking) = errors.split(|key, _| {
+        severities.severity(key) == Some(ErrorSeverity::Warning)
+    });
+
+    let keys = |errors: &Errors| {
+        errors
+            .iter()
+            .map(|(key, _)| key.as_str().to_string())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(keys(&warnings), ["email", "name"]);
+    assert_eq!(keys(&blocking), ["save"]);
+    assert_eq!(
+        warnings.severity(&ErrorKey::from("name")),
+        Some(ErrorSeverity::Warning)
+    );
+    assert_eq!(
+        blocking.severity(&ErrorKey::from("save")),
+        Some(ErrorSeverity::Error)
+    );
+}
+
+#[test]
+fn count_by_severity_counts_each_severity() {
+    let mut errors = Errors::default();
+    for key in ["a", "b"] {
+        errors.insert_with_severity(
+            ErrorKey::from(key),
+            TestError::new("warning"),
+            ErrorSeverity::Warning,
+        );
+    }
+    for key in ["c", "d", "e"] {
+        errors.insert(ErrorKey::from(key), TestError::new("error"));
+    }
+    errors.insert_with_severity(
+        ErrorKey::from("f"),
+        TestError::new("critical"),
+        ErrorSeverity::Critical,
+    );
+
+    let counts = errors.count_by_severity();
+    assert_eq!(counts.len(), 3);
+    assert_eq!(counts[&ErrorSeverity::Warning], 2);
+    assert_eq!(counts[&ErrorSeverity::Error], 3);
+    assert_eq!(counts[&ErrorSeverity::Critical], 1);
+    assert!(Errors::default().count_by_severity().is_empty());
+}
+
+#[derive(Debug)]
+struct Diagnostic {
+    lines: Vec<&'static str>,
+    multiline: bool,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))
+    }
+}
+
+impl Error for Diagnostic {}
+
+impl DisplayHint for Diagnostic {
+    fn multiline(&self) -> bool {
+        self.multiline
+    }
+}
+
+#[test]
+fn display_hint_chooses_block_rendering() {
+    let key = ErrorKey::from("build");
+    let diagnostic = |multiline| Diagnostic {
+        lines: vec!["error: expected `;`", "  --> src/main.rs:2:14"],
+        multiline,
+    };
+
+    _ = create_scope(create_runtime(), move |cx| {
+        let mut errors = Errors::default();
+        errors.insert_with_hint(key.clone(), diagnostic(true));
+        assert!(errors.is_multiline(&key));
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(html.contains("<pre"));
+
+        errors.insert_with_hint(key.clone(), diagnostic(false));
+        assert!(!errors.is_multiline(&key));
+        let html = errors_view(cx, &errors, &ErrorViewOptions::default())
+            .render_to_string(cx);
+        assert!(!html.contains("<pre"));
+
+        // types without a hint are single-line
+        errors.insert(key.clone(), diagnostic(true));
+        assert!(!errors.is_multiline(&key));
+        assert!(!Errors::default().is_multiline(&key));
+    });
+}
+
+#[test]
+fn state_hash_tracks_keys_and_messages() {
+    let mut errors = Errors::default();
+    let empty = errors.state_hash();
+    assert_eq!(empty, Errors::default().state_hash());
+
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+    let two = errors.state_hash();
+    assert_ne!(two, empty);
+
+    // the same keys and messages, inserted in another order
+    let mut same = Errors::default();
+    same.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+    same.insert(ErrorKey::from("email"), FieldError("email"));
+    assert_eq!(same.state_hash(), two);
+
+    errors.insert(ErrorKey::from("email"), FieldError("name"));
+    assert_ne!(errors.state_hash(), two);
+
+    errors.remove(&ErrorKey::from("email"));
+    errors.remove(&ErrorKey::from("fetch"));
+    assert_eq!(errors.state_hash(), empty);
+
+    // the same user-facing message, but a different `Display` output
+    let mut query = Errors::default();
+    query.insert_user_facing(ErrorKey::from("users"), QueryError);
+    let mut other = Errors::default();
+    other.insert(
+        ErrorKey::from("users"),
+        TestError::new("Could not load users."),
+    );
+    assert_ne!(query.state_hash(), other.state_hash());
+}
+
+#[test]
+fn sticky_errors_survive_ok_results() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        // renders the result at the same location each time
+        let render = |result: Result<(), FieldError>| {
+            HydrationCtx::continue_from(HydrationKey {
+                previous: "0-0-".to_string(),
+                offset: 0,
+            });
+            _ = result.into_view(cx);
+        };
+
+        render(Err(FieldError("email")));
+        let key = errors.with(|errors| errors.iter().next().unwrap().0.clone());
+        errors.update(|errors| {
+            errors.insert_sticky(key.clone(), FieldError("email"))
+        });
+
+        render(Ok(()));
+        assert!(errors.with(|errors| errors.iter().any(|(k, _)| k == &key)));
+
+        errors.update(Errors::clear_sticky);
+        assert!(errors.with(Errors::is_empty));
+
+        // without being sticky, the error is cleared
+        render(Err(FieldError("email")));
+        render(Ok(()));
+        assert!(errors.with(Errors::is_empty));
+    })
+    .dispose();
+
+    // nor when the scope of an error reported to a sink is disposed
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_error_sink(cx, errors);
+
+        let key = ErrorKey::from("email");
+        let (_, child) = cx.run_child_scope(|cx| {
+            report_error(cx, key.clone(), FieldError("email"));
+        });
+        errors.update(|errors| {
+            errors.insert_sticky(key.clone(), FieldError("email"))
+        });
+        child.dispose();
+        assert_eq!(errors.with(Errors::len), 1);
+
+        errors.update(Errors::clear_sticky);
+        let (_, child) = cx.run_child_scope(|cx| {
+            report_error(cx, key.clone(), FieldError("email"));
+        });
+        child.dispose();
+        assert!(errors.with(Errors::is_empty));
+    })
+    .dispose();
+}
+
+#[test]
+fn errors_convert_into_io_error() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+
+    let error = std::io::Error::from(errors);
+    assert_eq!(error.kind(), std::io::ErrorKind::Other);
+    assert_eq!(
+        error.to_string(),
+        "2 errors:\n  email: invalid field: email\n  fetch: request timed out"
+    );
+}
+
+#[test]
+fn is_subset_of_compares_messages() {
+    let mut produced = Errors::default();
+    produced.insert(ErrorKey::from("email"), FieldError("email"));
+    produced.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+
+    let mut expected = Errors::default();
+    expected
+        .insert(ErrorKey::from("fetch"), TestError::new("request timed out"));
+    assert!(expected.is_subset_of(&produced));
+    assert!(!produced.is_subset_of(&expected));
+    assert!(Errors::default().is_subset_of(&produced));
+
+    // same key, different message
+    expected.insert(ErrorKey::from("email"), FieldError("name"));
+    assert!(!expected.is_subset_of(&produced));
+
+    // a key that wasn't produced
+    let mut missing = Errors::default();
+    missing.insert(ErrorKey::from("name"), FieldError("name"));
+    assert!(!missing.is_subset_of(&produced));
+}
+
+#[test]
+fn contains_all_checks_keys() {
+    let mut errors = Errors::default();
+    errors.insert(ErrorKey::from("email"), FieldError("email"));
+    errors.insert(ErrorKey::from("fetch"), FetchError::Timeout);
+
+    assert!(errors.contains_all(&[ErrorKey::from("fetch")]));
+    assert!(errors
+        .contains_all(&[ErrorKey::from("email"), ErrorKey::from("fetch")]));
+    assert!(errors.contains_all(&[]));
+    assert!(!errors
+        .contains_all(&[ErrorKey::from("email"), ErrorKey::from("name")]));
+}
+
+#[derive(Debug)]
+struct CodeError(u16);
+
+impl fmt::Display for CodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "error code {}", self.0)
+    }
+}
+
+impl Error for CodeError {}
+
+#[cfg(not(feature = "ssr"))]
+#[test]
+fn async_error_formatter_resolves_messages() {
+    use leptos_dom::{
+        provide_async_error_formatter, use_async_error_formatter,
+    };
+
+    create_scope(create_runtime(), |cx| {
+        let plain = use_async_error_formatter(cx);
+        let message = plain(Arc::new(CodeError(404)));
+        assert_eq!(message.read(cx).as_deref(), Some("error code 404"));
+
+        provide_async_error_formatter(cx, |error| async move {
+            // stands in for a lookup on the server
+            match error.downcast_ref::<CodeError>() {
+                Some(CodeError(404)) => "Page not found".to_string(),
+                _ => error.to_string(),
+            }
+        });
+        let format = use_async_error_formatter(cx);
+        let message = format(Arc::new(CodeError(404)));
+        assert_eq!(message.read(cx).as_deref(), Some("Page not found"));
+        let message = format(Arc::new(CodeError(500)));
+        assert_eq!(message.read(cx).as_deref(), Some("error code 500"));
+    })
+    .dispose();
+}
+
+#[test]
+fn error_metrics_receive_counts() {
+    let counts = Arc::new(std::sync::Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&counts);
+    create_scope(create_runtime(), move |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+        provide_error_metrics(cx, move |count| {
+            recorded.lock().unwrap().push(count)
+        });
+
+        report_error(cx, ErrorKey::from("email"), FieldError("email"));
+        report_error(cx, ErrorKey::from("fetch"), FetchError::Timeout);
+        // replacing an error doesn't change the count
+        report_error(cx, ErrorKey::from("email"), FieldError("name"));
+        errors.update(|errors| {
+            errors.remove(&ErrorKey::from("fetch"));
+        });
+    })
+    .dispose();
+
+    // disposing the scope cleans up the error it reported last
+    assert_eq!(*counts.lock().unwrap(), [0, 1, 2, 1, 0]);
+}
+
+#[test]
+fn insert_with_skips_construction_for_existing_keys() {
+    let built = std::cell::Cell::new(0);
+    let build = || {
+        built.set(built.get() + 1);
+        FieldError("email")
+    };
+
+    let mut errors = Errors::default();
+    let key = ErrorKey::from("email");
+    assert!(errors.insert_with(key.clone(), build));
+    assert!(!errors.insert_with(key.clone(), build));
+    assert_eq!(built.get(), 1);
+    assert_eq!(errors.len(), 1);
+
+    assert!(errors.insert_with(ErrorKey::from("name"), build));
+    assert_eq!(built.get(), 2);
+}
+
+#[test]
+fn retryable_view_clears_the_error_on_success() {
+    create_scope(create_runtime(), |cx| {
+        let errors = create_rw_signal(cx, Errors::default());
+        provide_context(cx, errors);
+
+        let attempts = std::cell::Cell::new(0);
+        let (view, retry) = retryable_view(cx, move || {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                1 | 2 => Err(FetchError::Timeout),
+                _ => Ok("loaded"),
+            }
+        });
+        assert_eq!(errors.with(Errors::len), 1);
+        let key = errors.with(|errors| errors.iter().next().unwrap().0.clone());
+
+        // failing again replaces the error under the same key
+        retry();
+        errors.with(|errors| {
+            assert_eq!(errors.len(), 1);
+            assert_eq!(errors.iter().next().unwrap().0, &key);
+        });
+
+        retry();
+        assert!(errors.with(Errors::is_empty));
+        let html = view.into_view(cx).render_to_string(cx);
+        assert!(html.contains("loaded"));
+    })
+    .dispose();
+}
@@ -0,0 +1,100 @@
+//! Counts the allocations made while deriving and looking up error keys,
+//! which happens for every `Result` view inside an `<ErrorBoundary/>`.
+//!
+//! This is a separate test binary, since it replaces the global allocator.
+
+use leptos_dom::{ErrorKey, Errors, IntoView};
+use leptos_reactive::{
+    create_runtime, create_rw_signal, create_scope, provide_context,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+    error::Error,
+    fmt,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(
+        &self,
+        ptr: *mut u8,
+        layout: Layout,
+        new_size: usize,
+    ) -> *mut u8 {
+        _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations `f` made on this thread.
+fn allocations<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let value = f();
+    (value, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[derive(Debug)]
+struct FieldError;
+
+impl fmt::Display for FieldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("invalid field")
+    }
+}
+
+impl Error for FieldError {}
+
+#[test]
+fn static_keys_never_allocate() {
+    let (key, count) = allocations(|| ErrorKey::from_static("email"));
+    assert_eq!(count, 0);
+    let (_, count) = allocations(|| ErrorKey::from("email"));
+    assert_eq!(count, 0);
+    let (_, count) = allocations(|| key.clone());
+    assert_eq!(count, 0);
+
+    let mut errors = Errors::default();
+    errors.insert(key.clone(), FieldError);
+    let (severity, count) = allocations(|| errors.severity(&key));
+    assert!(severity.is_some());
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn ok_views_do_not_allocate_their_key() {
+    create_scope(create_runtime(), |cx| {
+        let render = || Ok::<_, FieldError>("fine").into_view(cx);
+        let (_, plain) = allocations(|| "fine".into_view(cx));
+
+        // without a boundary, no key is derived at all
+        let (_, count) = allocations(render);
+        assert_eq!(count, plain);
+
+        provide_context(cx, create_rw_signal(cx, Errors::default()));
+        // warm up the buffers that keys are written to
+        _ = render();
+        for _ in 0..10 {
+            let (_, count) = allocations(render);
+            assert_eq!(count, plain);
+        }
+    })
+    .dispose();
+}